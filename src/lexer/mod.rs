@@ -1,5 +1,7 @@
 pub use token::{LiteralTypes, Token, TokenType};
 
+use crate::number::{BigInt, Decimal};
+
 mod token;
 
 pub struct Lexer {
@@ -46,56 +48,122 @@ impl Lexer {
             b')' => self.add_token(TokenType::RightParen, LiteralTypes::Nil),
             b'{' => self.add_token(TokenType::LeftBrace, LiteralTypes::Nil),
             b'}' => self.add_token(TokenType::RightBrace, LiteralTypes::Nil),
+            b'[' => self.add_token(TokenType::LeftBracket, LiteralTypes::Nil),
+            b']' => self.add_token(TokenType::RightBracket, LiteralTypes::Nil),
             b',' => self.add_token(TokenType::Comma, LiteralTypes::Nil),
-            b'.' => self.add_token(TokenType::Dot, LiteralTypes::Nil),
-            b'-' => self.add_token(TokenType::Minus, LiteralTypes::Nil),
-            b'+' => self.add_token(TokenType::Plus, LiteralTypes::Nil),
+            b'.' => {
+                let ttype = if self.is_next_expected(b'.') {
+                    if self.is_next_expected(b'.') {
+                        TokenType::DotDotDot
+                    } else if self.is_next_expected(b'=') {
+                        TokenType::DotDotEqual
+                    } else {
+                        TokenType::DotDot
+                    }
+                } else {
+                    TokenType::Dot
+                };
+                self.add_token(ttype, LiteralTypes::Nil);
+            }
+            b'-' => {
+                let ttype = if self.is_next_expected(b'-') {
+                    TokenType::MinusMinus
+                } else if self.is_next_expected(b'=') {
+                    TokenType::MinusEqual
+                } else {
+                    TokenType::Minus
+                };
+                self.add_token(ttype, LiteralTypes::Nil);
+            }
+            b'+' => {
+                let ttype = if self.is_next_expected(b'+') {
+                    TokenType::PlusPlus
+                } else if self.is_next_expected(b'=') {
+                    TokenType::PlusEqual
+                } else {
+                    TokenType::Plus
+                };
+                self.add_token(ttype, LiteralTypes::Nil);
+            }
             b';' => self.add_token(TokenType::Semicolon, LiteralTypes::Nil),
-            b'*' => self.add_token(TokenType::Star, LiteralTypes::Nil),
-
-            b'!' => {
-                let is_equal = self.is_next_expected(b'=');
+            b'*' => {
+                let ttype = if self.is_next_expected(b'*') {
+                    TokenType::StarStar
+                } else if self.is_next_expected(b'=') {
+                    TokenType::StarEqual
+                } else {
+                    TokenType::Star
+                };
+                self.add_token(ttype, LiteralTypes::Nil);
+            }
+            b'~' => {
+                let is_slash = self.is_next_expected(b'/');
                 self.add_token(
-                    if is_equal {
-                        TokenType::BangEqual
+                    if is_slash {
+                        TokenType::TildeSlash
                     } else {
-                        TokenType::Bang
+                        TokenType::Tilde
                     },
                     LiteralTypes::Nil,
                 );
             }
-            b'=' => {
+            b'&' => self.add_token(TokenType::Amp, LiteralTypes::Nil),
+            b'^' => self.add_token(TokenType::Caret, LiteralTypes::Nil),
+            b'%' => {
                 let is_equal = self.is_next_expected(b'=');
                 self.add_token(
                     if is_equal {
-                        TokenType::EqualEqual
+                        TokenType::PercentEqual
                     } else {
-                        TokenType::Equal
+                        TokenType::Percent
                     },
                     LiteralTypes::Nil,
                 );
             }
-            b'<' => {
+            b'|' => self.add_token(TokenType::Pipe, LiteralTypes::Nil),
+            b'?' => self.add_token(TokenType::Question, LiteralTypes::Nil),
+            b':' => self.add_token(TokenType::Colon, LiteralTypes::Nil),
+
+            b'!' => {
                 let is_equal = self.is_next_expected(b'=');
                 self.add_token(
                     if is_equal {
-                        TokenType::LessEqual
+                        TokenType::BangEqual
                     } else {
-                        TokenType::Less
+                        TokenType::Bang
                     },
                     LiteralTypes::Nil,
                 );
             }
+            b'=' => {
+                let ttype = if self.is_next_expected(b'=') {
+                    TokenType::EqualEqual
+                } else if self.is_next_expected(b'>') {
+                    TokenType::FatArrow
+                } else {
+                    TokenType::Equal
+                };
+                self.add_token(ttype, LiteralTypes::Nil);
+            }
+            b'<' => {
+                let ttype = if self.is_next_expected(b'=') {
+                    TokenType::LessEqual
+                } else if self.is_next_expected(b'<') {
+                    TokenType::LessLess
+                } else {
+                    TokenType::Less
+                };
+                self.add_token(ttype, LiteralTypes::Nil);
+            }
             b'>' => {
-                let is_equal = self.is_next_expected(b'=');
-                self.add_token(
-                    if is_equal {
-                        TokenType::GreaterEqual
-                    } else {
-                        TokenType::Greater
-                    },
-                    LiteralTypes::Nil,
-                );
+                let ttype = if self.is_next_expected(b'=') {
+                    TokenType::GreaterEqual
+                } else if self.is_next_expected(b'>') {
+                    TokenType::GreaterGreater
+                } else {
+                    TokenType::Greater
+                };
+                self.add_token(ttype, LiteralTypes::Nil);
             }
             b'/' => {
                 let slash = self.is_next_expected(b'/');
@@ -103,6 +171,8 @@ impl Lexer {
                     while self.peek() != b'\n' && !self.is_at_end() {
                         self.advance();
                     }
+                } else if self.is_next_expected(b'=') {
+                    self.add_token(TokenType::SlashEqual, LiteralTypes::Nil)
                 } else {
                     self.add_token(TokenType::Slash, LiteralTypes::Nil)
                 }
@@ -157,14 +227,14 @@ impl Lexer {
         if self.is_at_end() {
             return b'\0';
         }
-        return self.source.as_bytes()[self.current];
+        self.source.as_bytes()[self.current]
     }
 
     fn peek_next(&self) -> u8 {
-        if self.is_at_end() {
+        if self.current + 1 >= self.source.len() {
             return b'\0';
         }
-        return self.source.as_bytes()[self.current + 1];
+        self.source.as_bytes()[self.current + 1]
     }
 
     fn string(&mut self) {
@@ -183,12 +253,16 @@ impl Lexer {
         }
     }
 
+    // Literals without a fractional part are ints (big ints past the i64 range), the
+    // others floats. A `d` suffix makes either a decimal: `12.50d`.
     fn number(&mut self) {
         while self.peek().is_ascii_digit() {
             self.current += 1;
         }
 
+        let mut is_float = false;
         if self.peek() == b'.' && self.peek_next().is_ascii_digit() {
+            is_float = true;
             self.current += 1;
 
             while self.peek().is_ascii_digit() {
@@ -196,8 +270,25 @@ impl Lexer {
             }
         }
 
-        let value: f64 = self.source[self.start..self.current].parse().unwrap();
-        self.add_token(TokenType::Number, LiteralTypes::Number(value))
+        let text = self.source[self.start..self.current].to_string();
+        let next = self.peek_next();
+        if self.peek() == b'd' && !self.is_alpha(next) && !next.is_ascii_digit() {
+            self.current += 1;
+            let value = Decimal::parse(&text).unwrap();
+            return self.add_token(TokenType::Number, LiteralTypes::Decimal(value));
+        }
+
+        if is_float {
+            let value: f64 = text.parse().unwrap();
+            return self.add_token(TokenType::Number, LiteralTypes::Number(value));
+        }
+        match text.parse() {
+            Ok(value) => self.add_token(TokenType::Number, LiteralTypes::Int(value)),
+            Err(_) => {
+                let value = BigInt::parse(&text).unwrap();
+                self.add_token(TokenType::Number, LiteralTypes::BigInt(value))
+            }
+        }
     }
 
     // Check for either identifier or keywords
@@ -229,19 +320,28 @@ impl Lexer {
     fn get_keyword(&self, word: &str) -> Option<TokenType> {
         match word {
             "and" => Some(TokenType::And),
+            "catch" => Some(TokenType::Catch),
             "class" => Some(TokenType::Class),
+            "const" => Some(TokenType::Const),
             "else" => Some(TokenType::Else),
+            "enum" => Some(TokenType::Enum),
             "false" => Some(TokenType::False),
+            "finally" => Some(TokenType::Finally),
             "for" => Some(TokenType::For),
             "fun" => Some(TokenType::Fun),
             "if" => Some(TokenType::If),
+            "import" => Some(TokenType::Import),
+            "in" => Some(TokenType::In),
+            "match" => Some(TokenType::Match),
             "nil" => Some(TokenType::Nil),
             "or" => Some(TokenType::Or),
             "print" => Some(TokenType::Print),
             "return" => Some(TokenType::Return),
             "super" => Some(TokenType::Super),
             "this" => Some(TokenType::This),
+            "throw" => Some(TokenType::Throw),
             "true" => Some(TokenType::True),
+            "try" => Some(TokenType::Try),
             "var" => Some(TokenType::Var),
             "while" => Some(TokenType::While),
             _ => None,
@@ -264,6 +364,14 @@ mod tests {
             1,
         )
     }
+    fn int_token(lexeme: &str, number: i64) -> Token {
+        Token::new(
+            TokenType::Number,
+            lexeme.to_string(),
+            LiteralTypes::Int(number),
+            1,
+        )
+    }
     fn string_token(lexeme: &str, value: &str) -> Token {
         Token::new(
             TokenType::String,
@@ -287,7 +395,7 @@ mod tests {
         assert_eq!(
             tokens,
             vec![
-                number_token("1", 1.0),
+                int_token("1", 1),
                 normal_token(TokenType::Plus, "+"),
                 iden_token("a"),
                 normal_token(TokenType::Minus, "-"),
@@ -337,4 +445,108 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_match_tokens() {
+        let tokens = Lexer::new("1..=2 | _ => 3.. ...").scan_tokens();
+        assert_eq!(
+            tokens,
+            vec![
+                int_token("1", 1),
+                normal_token(TokenType::DotDotEqual, "..="),
+                int_token("2", 2),
+                normal_token(TokenType::Pipe, "|"),
+                iden_token("_"),
+                normal_token(TokenType::FatArrow, "=>"),
+                int_token("3", 3),
+                normal_token(TokenType::DotDot, ".."),
+                normal_token(TokenType::DotDotDot, "..."),
+                normal_token(TokenType::Eof, "")
+            ]
+        )
+    }
+
+    #[test]
+    fn test_compound_tokens() {
+        let tokens = Lexer::new("a += 1; a-- %= /=").scan_tokens();
+        assert_eq!(
+            tokens,
+            vec![
+                iden_token("a"),
+                normal_token(TokenType::PlusEqual, "+="),
+                int_token("1", 1),
+                normal_token(TokenType::Semicolon, ";"),
+                iden_token("a"),
+                normal_token(TokenType::MinusMinus, "--"),
+                normal_token(TokenType::PercentEqual, "%="),
+                normal_token(TokenType::SlashEqual, "/="),
+                normal_token(TokenType::Eof, "")
+            ]
+        )
+    }
+
+    #[test]
+    fn test_arithmetic_tokens() {
+        let tokens = Lexer::new("2 ** 3 ~/ 4 % 5").scan_tokens();
+        assert_eq!(
+            tokens,
+            vec![
+                int_token("2", 2),
+                normal_token(TokenType::StarStar, "**"),
+                int_token("3", 3),
+                normal_token(TokenType::TildeSlash, "~/"),
+                int_token("4", 4),
+                normal_token(TokenType::Percent, "%"),
+                int_token("5", 5),
+                normal_token(TokenType::Eof, "")
+            ]
+        )
+    }
+
+    #[test]
+    fn test_bitwise_tokens() {
+        let tokens = Lexer::new("~a & b ^ c << d >> e").scan_tokens();
+        assert_eq!(
+            tokens,
+            vec![
+                normal_token(TokenType::Tilde, "~"),
+                iden_token("a"),
+                normal_token(TokenType::Amp, "&"),
+                iden_token("b"),
+                normal_token(TokenType::Caret, "^"),
+                iden_token("c"),
+                normal_token(TokenType::LessLess, "<<"),
+                iden_token("d"),
+                normal_token(TokenType::GreaterGreater, ">>"),
+                iden_token("e"),
+                normal_token(TokenType::Eof, "")
+            ]
+        )
+    }
+
+    #[test]
+    fn test_int_and_float() {
+        let tokens = Lexer::new("3 3.0 99999999999999999999 12.50d 7d 7do").scan_tokens();
+        let exact =
+            |lexeme: &str, literal| Token::new(TokenType::Number, lexeme.to_string(), literal, 1);
+        assert_eq!(
+            tokens,
+            vec![
+                int_token("3", 3),
+                number_token("3.0", 3.0),
+                exact(
+                    "99999999999999999999",
+                    LiteralTypes::BigInt(BigInt::parse("99999999999999999999").unwrap())
+                ),
+                exact(
+                    "12.50d",
+                    LiteralTypes::Decimal(Decimal::parse("12.50").unwrap())
+                ),
+                exact("7d", LiteralTypes::Decimal(Decimal::parse("7").unwrap())),
+                int_token("7", 7),
+                iden_token("do"),
+                normal_token(TokenType::Eof, "")
+            ]
+        )
+    }
 }
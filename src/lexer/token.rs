@@ -1,3 +1,11 @@
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+use crate::{
+    number::{BigInt, Decimal},
+    parser::{Callable, EnumType, ErrorObject, Map, Module, Range, Variant},
+    regex::Regex,
+};
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub ttype: TokenType,
@@ -10,9 +18,28 @@ pub struct Token {
 pub enum LiteralTypes {
     String(String),
     Number(f64),
+    Int(i64),
+    /// An integer outside the `i64` range; smaller values are always `Int`.
+    BigInt(BigInt),
+    Decimal(Decimal),
     Bool(bool),
     Nil,
-    //Callable(Callable),
+    /// Lists are shared by reference, like environments.
+    List(Rc<RefCell<Vec<LiteralTypes>>>),
+    Map(Rc<RefCell<Map>>),
+    Range(Range),
+    /// Fixed-size and immutable, so the elements can be shared freely.
+    Tuple(Rc<[LiteralTypes]>),
+    /// The type created by an `enum` declaration, e.g. `Color`.
+    Enum(Rc<EnumType>),
+    Variant(Rc<Variant>),
+    /// A caught error, as bound by `catch (e)`.
+    Error(Rc<ErrorObject>),
+    /// What `import "path" as name;` binds.
+    Module(Rc<Module>),
+    Callable(Callable),
+    /// A compiled pattern, as returned by `re.compile`.
+    Regex(Rc<Regex>),
 }
 
 impl Token {
@@ -36,27 +63,51 @@ impl Token {
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     // Single-character tokens.
-    LeftParen,  // (
-    RightParen, // )
-    LeftBrace,  // {
-    RightBrace, // }
-    Comma,      // ,
-    Dot,        // .
-    Minus,      // -
-    Plus,       // +
-    Semicolon,  // ;
-    Slash,      // /
-    Star,       // *
+    LeftParen,    // (
+    RightParen,   // )
+    LeftBrace,    // {
+    RightBrace,   // }
+    LeftBracket,  // [
+    RightBracket, // ]
+    Comma,        // ,
+    Dot,          // .
+    Minus,        // -
+    Plus,         // +
+    Semicolon,    // ;
+    Slash,        // /
+    Star,         // *
+    Percent,      // %
+    Pipe,         // |
+    Question,     // ?
+    Colon,        // :
+    Amp,          // &
+    Caret,        // ^
+    Tilde,        // ~
 
     // One or two character tokens.
-    Bang,         // !
-    BangEqual,    // !=
-    Equal,        // =
-    EqualEqual,   // ==
-    Greater,      // >
-    GreaterEqual, // >=
-    Less,         // <
-    LessEqual,    // <=
+    Bang,           // !
+    BangEqual,      // !=
+    Equal,          // =
+    EqualEqual,     // ==
+    Greater,        // >
+    GreaterEqual,   // >=
+    Less,           // <
+    LessEqual,      // <=
+    LessLess,       // <<
+    GreaterGreater, // >>
+    FatArrow,       // =>
+    StarStar,       // **
+    TildeSlash,     // ~/
+    DotDot,         // ..
+    DotDotEqual,    // ..=
+    DotDotDot,      // ...
+    PlusEqual,      // +=
+    MinusEqual,     // -=
+    StarEqual,      // *=
+    SlashEqual,     // /=
+    PercentEqual,   // %=
+    PlusPlus,       // ++
+    MinusMinus,     // --
 
     // Literals.
     Identifier,
@@ -65,19 +116,28 @@ pub enum TokenType {
 
     // Keywords.
     And,
+    Catch,
     Class,
+    Const,
     Else,
+    Enum,
     False,
+    Finally,
     Fun,
     For,
     If,
+    Import,
+    In,
+    Match,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
 
@@ -87,23 +147,173 @@ pub enum TokenType {
 
 impl LiteralTypes {
     pub fn equal(&self, rhs: &LiteralTypes) -> bool {
+        self.equal_in(rhs, &mut Vec::new())
+    }
+
+    /// `equal`, given the pairs of containers being compared further out. A pair met
+    /// again is taken as equal, so comparing values that contain themselves ends.
+    fn equal_in(&self, rhs: &LiteralTypes, seen: &mut Vec<(*const (), *const ())>) -> bool {
         match (self, rhs) {
             (LiteralTypes::Number(left), LiteralTypes::Number(right)) => left == right,
+            (LiteralTypes::Int(left), LiteralTypes::Int(right)) => left == right,
+            (left, right) if left.as_f64().is_some() && right.as_f64().is_some() => {
+                self.compare(rhs) == Some(Ordering::Equal)
+            }
             (LiteralTypes::String(left), LiteralTypes::String(right)) => left == right,
             (LiteralTypes::Bool(left), LiteralTypes::Bool(right)) => left == right,
             (LiteralTypes::Nil, LiteralTypes::Nil) => true,
-            (LiteralTypes::Nil, LiteralTypes::Bool(b)) if *b == false => true,
-            (LiteralTypes::Bool(b), LiteralTypes::Nil) if *b == false => true,
+            (LiteralTypes::Nil, LiteralTypes::Bool(b)) if !*b => true,
+            (LiteralTypes::Bool(b), LiteralTypes::Nil) if !*b => true,
+            (LiteralTypes::List(left), LiteralTypes::List(right)) => {
+                let pair = (
+                    Rc::as_ptr(left) as *const (),
+                    Rc::as_ptr(right) as *const (),
+                );
+                if Rc::ptr_eq(left, right) || seen.contains(&pair) {
+                    return true;
+                }
+                seen.push(pair);
+                let (left, right) = (left.borrow(), right.borrow());
+                let equal = left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(l, r)| l.equal_in(r, seen));
+                seen.pop();
+                equal
+            }
+            (LiteralTypes::Range(left), LiteralTypes::Range(right)) => left == right,
+            (LiteralTypes::Enum(left), LiteralTypes::Enum(right)) => Rc::ptr_eq(left, right),
+            (LiteralTypes::Error(left), LiteralTypes::Error(right)) => Rc::ptr_eq(left, right),
+            (LiteralTypes::Module(left), LiteralTypes::Module(right)) => Rc::ptr_eq(left, right),
+            (LiteralTypes::Callable(left), LiteralTypes::Callable(right)) => left == right,
+            (LiteralTypes::Regex(left), LiteralTypes::Regex(right)) => left == right,
+            (LiteralTypes::Variant(left), LiteralTypes::Variant(right)) => {
+                left.equal(right, |l, r| l.equal_in(r, seen))
+            }
+            (LiteralTypes::Tuple(left), LiteralTypes::Tuple(right)) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(l, r)| l.equal_in(r, seen))
+            }
+            (LiteralTypes::Map(left), LiteralTypes::Map(right)) => {
+                let pair = (
+                    Rc::as_ptr(left) as *const (),
+                    Rc::as_ptr(right) as *const (),
+                );
+                if Rc::ptr_eq(left, right) || seen.contains(&pair) {
+                    return true;
+                }
+                seen.push(pair);
+                let (left, right) = (left.borrow(), right.borrow());
+                let equal = left.len() == right.len()
+                    && left.entries().iter().all(|(key, value)| {
+                        matches!(right.get(key), Ok(Some(other)) if value.equal_in(&other, seen))
+                    });
+                seen.pop();
+                equal
+            }
             _ => false,
         }
     }
 
     pub fn stringify(&self) -> String {
+        self.stringify_in(&mut Vec::new())
+    }
+
+    /// `stringify` inside the containers at the addresses in `seen`, which come out as
+    /// `[...]` or `{...}` when met again.
+    fn stringify_in(&self, seen: &mut Vec<*const ()>) -> String {
         match self {
+            // keep a trailing `.0` so `3.0` can't be mistaken for the int `3`
+            LiteralTypes::Number(num) if num.is_finite() && num.fract() == 0.0 => {
+                format!("{:.1}", num)
+            }
             LiteralTypes::Number(num) => num.to_string(),
+            LiteralTypes::Int(num) => num.to_string(),
+            LiteralTypes::BigInt(num) => num.to_string(),
+            LiteralTypes::Decimal(num) => num.to_string(),
             LiteralTypes::Bool(b) => b.to_string(),
             LiteralTypes::Nil => "Nil".to_string(),
             LiteralTypes::String(str) => format!("\"{}\"", str),
+            LiteralTypes::List(list) => {
+                let address = Rc::as_ptr(list) as *const ();
+                let elements = match list.try_borrow() {
+                    Ok(list) if !seen.contains(&address) => list,
+                    // a list inside itself, or one in the middle of a change
+                    _ => return "[...]".to_string(),
+                };
+                seen.push(address);
+                let items: Vec<String> = elements
+                    .iter()
+                    .map(|item| item.stringify_in(seen))
+                    .collect();
+                seen.pop();
+                format!("[{}]", items.join(", "))
+            }
+            LiteralTypes::Map(map) => {
+                let address = Rc::as_ptr(map) as *const ();
+                let map = match map.try_borrow() {
+                    Ok(map) if !seen.contains(&address) => map,
+                    _ => return "{...}".to_string(),
+                };
+                seen.push(address);
+                let entries: Vec<String> = map
+                    .entries()
+                    .iter()
+                    .map(|(key, value)| {
+                        format!("{}: {}", key.stringify_in(seen), value.stringify_in(seen))
+                    })
+                    .collect();
+                seen.pop();
+                format!("{{{}}}", entries.join(", "))
+            }
+            LiteralTypes::Range(range) => range.to_string(),
+            LiteralTypes::Enum(ty) => format!("<enum {}>", ty.name),
+            LiteralTypes::Variant(variant) => variant.render(|item| item.stringify_in(seen)),
+            LiteralTypes::Error(error) => format!("<{}>", error),
+            LiteralTypes::Module(module) => format!("<module {}>", module.name()),
+            LiteralTypes::Callable(callable) => callable.to_string(),
+            LiteralTypes::Regex(regex) => format!("<regex /{}/>", regex.pattern()),
+            LiteralTypes::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.stringify_in(seen)).collect();
+                match items.as_slice() {
+                    // the trailing comma tells `(1,)` apart from a parenthesised `1`
+                    [item] => format!("({},)", item),
+                    _ => format!("({})", items.join(", ")),
+                }
+            }
+        }
+    }
+
+    /// Like `stringify`, but strings come out as their raw text.
+    pub fn to_text(&self) -> String {
+        match self {
+            LiteralTypes::String(str) => str.clone(),
+            _ => self.stringify(),
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            LiteralTypes::String(_) => "string",
+            LiteralTypes::Number(_) => "float",
+            LiteralTypes::Int(_) | LiteralTypes::BigInt(_) => "int",
+            LiteralTypes::Decimal(_) => "decimal",
+            LiteralTypes::Bool(_) => "bool",
+            LiteralTypes::Nil => "nil",
+            LiteralTypes::List(_) => "list",
+            LiteralTypes::Map(_) => "map",
+            LiteralTypes::Range(_) => "range",
+            LiteralTypes::Tuple(_) => "tuple",
+            LiteralTypes::Enum(_) => "enum",
+            LiteralTypes::Variant(_) => "variant",
+            LiteralTypes::Error(_) => "error",
+            LiteralTypes::Module(_) => "module",
+            LiteralTypes::Callable(_) => "function",
+            LiteralTypes::Regex(_) => "regex",
         }
     }
 
@@ -115,3 +325,79 @@ impl LiteralTypes {
         }
     }
 }
+
+impl LiteralTypes {
+    /// The value of any numeric type as a float, for mixed arithmetic.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            LiteralTypes::Number(num) => Some(*num),
+            LiteralTypes::Int(num) => Some(*num as f64),
+            LiteralTypes::BigInt(num) => Some(num.to_f64()),
+            LiteralTypes::Decimal(num) => Some(num.to_f64()),
+            _ => None,
+        }
+    }
+
+    /// `Int` and `BigInt` values as a `BigInt`.
+    pub fn to_bigint(&self) -> Option<BigInt> {
+        match self {
+            LiteralTypes::Int(num) => Some(BigInt::from(*num)),
+            LiteralTypes::BigInt(num) => Some(num.clone()),
+            _ => None,
+        }
+    }
+
+    /// The exact numeric types (`Int`, `BigInt`, `Decimal`) as a `Decimal`.
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        match self {
+            LiteralTypes::Decimal(num) => Some(num.clone()),
+            _ => self.to_bigint().map(Decimal::from),
+        }
+    }
+
+    /// An integer result: `Int` when it fits in `i64`, `BigInt` otherwise.
+    pub fn integer(value: BigInt) -> LiteralTypes {
+        match value.to_i64() {
+            Some(num) => LiteralTypes::Int(num),
+            None => LiteralTypes::BigInt(value),
+        }
+    }
+
+    /// Order two numeric values. The exact types compare exactly among themselves, and
+    /// so do `Int` and `Number`; `BigInt` and `Decimal` against `Number` go through
+    /// `f64`. Non-numbers and `NaN` are unordered.
+    pub fn compare(&self, rhs: &LiteralTypes) -> Option<Ordering> {
+        match (self, rhs) {
+            (LiteralTypes::Int(left), LiteralTypes::Int(right)) => Some(left.cmp(right)),
+            (LiteralTypes::Number(left), LiteralTypes::Number(right)) => left.partial_cmp(right),
+            (LiteralTypes::Int(left), LiteralTypes::Number(right)) => {
+                compare_int_float(*left, *right)
+            }
+            (LiteralTypes::Number(left), LiteralTypes::Int(right)) => {
+                compare_int_float(*right, *left).map(Ordering::reverse)
+            }
+            (LiteralTypes::Number(_), _) | (_, LiteralTypes::Number(_)) => {
+                self.as_f64()?.partial_cmp(&rhs.as_f64()?)
+            }
+            _ => Some(self.to_decimal()?.cmp(&rhs.to_decimal()?)),
+        }
+    }
+}
+
+fn compare_int_float(int: i64, float: f64) -> Option<Ordering> {
+    // 2^63: every i64 is below it and every float at or above it is out of range
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if float.is_nan() {
+        return None;
+    }
+    if float >= LIMIT {
+        return Some(Ordering::Less);
+    }
+    if float < -LIMIT {
+        return Some(Ordering::Greater);
+    }
+
+    let whole = float.trunc();
+    let fraction = float - whole;
+    Some(int.cmp(&(whole as i64)).then(0.0.partial_cmp(&fraction)?))
+}
@@ -1,7 +1,11 @@
 use lexer::{Token, TokenType};
 
+pub use parser::{run, run_file, run_in, Access, Clock, FakeClock, Interpreter, SystemClock};
+
 mod lexer;
+mod number;
 mod parser;
+mod regex;
 
 // For handling language errors
 pub fn report(line: usize, message: &str) {
@@ -0,0 +1,11 @@
+use std::{path::Path, process};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(script) = args.next() else {
+        eprintln!("Usage: rlox <script> [arguments...]");
+        process::exit(64);
+    };
+
+    process::exit(rlox::run_file(Path::new(&script), args.collect()));
+}
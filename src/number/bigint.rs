@@ -0,0 +1,434 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+/// Arbitrary-precision integer: a sign and a little-endian base-2^32 magnitude.
+///
+/// The magnitude never has trailing zero limbs and zero is never negative, so the derived
+/// `PartialEq`/`Hash` agree with numeric equality.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// `10^exp`
+    pub fn pow10(exp: u32) -> Self {
+        BigInt::from(10).pow(exp)
+    }
+
+    /// Parse an optionally signed run of decimal digits.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut magnitude = Vec::new();
+        for chunk in digits.as_bytes().chunks(9) {
+            let chunk = std::str::from_utf8(chunk).ok()?;
+            let value: u32 = chunk.parse().ok()?;
+            mul_small(&mut magnitude, 10u32.pow(chunk.len() as u32));
+            add_small(&mut magnitude, value);
+        }
+
+        Some(Self::from_parts(negative, magnitude))
+    }
+
+    /// The value of a whole, finite float.
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+        if value.abs() < 9.0e18 {
+            return Some(BigInt::from(value as i64));
+        }
+
+        // |value| >= 2^52 here, so it is exactly mantissa * 2^exponent with exponent > 0
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as u32 - 1075;
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let magnitude = &BigInt::from(mantissa as i64) * &BigInt::from(2).pow(exponent);
+        Some(if value < 0.0 { -&magnitude } else { magnitude })
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0u64, |acc, d| (acc << 32) | *d as u64);
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0f64, |acc, d| acc * 4_294_967_296.0 + *d as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::from_parts(false, self.digits.clone())
+    }
+
+    pub fn pow(&self, mut exp: u32) -> Self {
+        let mut base = self.clone();
+        let mut result = BigInt::from(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Truncating division, like Rust's `/` and `%` on primitives.
+    ///
+    /// Returns `None` when `rhs` is zero.
+    pub fn div_rem(&self, rhs: &BigInt) -> Option<(BigInt, BigInt)> {
+        if rhs.is_zero() {
+            return None;
+        }
+        let (quot, rem) = div_rem_magnitude(&self.digits, &rhs.digits);
+        Some((
+            Self::from_parts(self.negative != rhs.negative, quot),
+            Self::from_parts(self.negative, rem),
+        ))
+    }
+
+    /// Floored division: the quotient rounds towards negative infinity and the remainder
+    /// takes the sign of the divisor.
+    pub fn div_mod_floor(&self, rhs: &BigInt) -> Option<(BigInt, BigInt)> {
+        let (quot, rem) = self.div_rem(rhs)?;
+        if !rem.is_zero() && rem.negative != rhs.negative {
+            Some((&quot - &BigInt::from(1), &rem + rhs))
+        } else {
+            Some((quot, rem))
+        }
+    }
+
+    fn from_parts(negative: bool, mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        let negative = negative && !digits.is_empty();
+        Self { negative, digits }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        Self::from_parts(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.digits, &other.digits),
+            (true, true) => cmp_magnitude(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.digits.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, rhs: &BigInt) -> BigInt {
+        if self.negative == rhs.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.digits, &rhs.digits));
+        }
+        match cmp_magnitude(&self.digits, &rhs.digits) {
+            Ordering::Less => {
+                BigInt::from_parts(rhs.negative, sub_magnitude(&rhs.digits, &self.digits))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.digits, &rhs.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, rhs: &BigInt) -> BigInt {
+        self + &-rhs
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, rhs: &BigInt) -> BigInt {
+        let mut product = vec![0u32; self.digits.len() + rhs.digits.len()];
+        for (i, a) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in rhs.digits.iter().enumerate() {
+                let cell = product[i + j] as u64 + *a as u64 * *b as u64 + carry;
+                product[i + j] = cell as u32;
+                carry = cell >> 32;
+            }
+            product[i + rhs.digits.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != rhs.negative, product)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // peel off base-10^9 chunks, least significant first
+        let mut chunks = Vec::new();
+        let mut magnitude = self.digits.clone();
+        while !magnitude.is_empty() {
+            chunks.push(div_small(&mut magnitude, 1_000_000_000));
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, digit) in long.iter().enumerate() {
+        let cell = *digit as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(cell as u32);
+        carry = cell >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// `a - b`, requires `|a| >= |b|`
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, digit) in a.iter().enumerate() {
+        let mut cell = *digit as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if cell < 0 {
+            cell += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(cell as u32);
+    }
+    difference
+}
+
+fn mul_small(magnitude: &mut Vec<u32>, factor: u32) {
+    let mut carry = 0u64;
+    for digit in magnitude.iter_mut() {
+        let cell = *digit as u64 * factor as u64 + carry;
+        *digit = cell as u32;
+        carry = cell >> 32;
+    }
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+fn add_small(magnitude: &mut Vec<u32>, value: u32) {
+    let mut carry = value as u64;
+    for digit in magnitude.iter_mut() {
+        if carry == 0 {
+            return;
+        }
+        let cell = *digit as u64 + carry;
+        *digit = cell as u32;
+        carry = cell >> 32;
+    }
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+/// Divide in place by `divisor`, returning the remainder.
+fn div_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut rem = 0u64;
+    for digit in magnitude.iter_mut().rev() {
+        let cell = (rem << 32) | *digit as u64;
+        *digit = (cell / divisor as u64) as u32;
+        rem = cell % divisor as u64;
+    }
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    rem as u32
+}
+
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let mut quot = a.to_vec();
+        let rem = div_small(&mut quot, b[0]);
+        return (quot, vec![rem]);
+    }
+
+    // schoolbook binary long division
+    let mut quot = vec![0u32; a.len()];
+    let mut rem: Vec<u32> = Vec::new();
+    for bit in (0..a.len() * 32).rev() {
+        mul_small(&mut rem, 2);
+        if (a[bit / 32] >> (bit % 32)) & 1 == 1 {
+            add_small(&mut rem, 1);
+        }
+        if cmp_magnitude(&rem, b) != Ordering::Less {
+            rem = sub_magnitude(&rem, b);
+            while rem.last() == Some(&0) {
+                rem.pop();
+            }
+            quot[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quot, rem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(text: &str) -> BigInt {
+        BigInt::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for text in [
+            "0",
+            "7",
+            "-42",
+            "18446744073709551616",
+            "-123456789012345678901234567890",
+        ] {
+            assert_eq!(big(text).to_string(), text);
+        }
+        assert_eq!(big("-0"), BigInt::zero());
+        assert_eq!(big("000123").to_string(), "123");
+        assert!(BigInt::parse("12a").is_none());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = big("9223372036854775807");
+        let b = big("-18446744073709551616");
+        assert_eq!((&a + &BigInt::from(1)).to_string(), "9223372036854775808");
+        assert_eq!((&a + &b).to_string(), "-9223372036854775809");
+        assert_eq!((&b - &a).to_string(), "-27670116110564327423");
+        assert_eq!(
+            (&a * &b).to_string(),
+            "-170141183460469231713240559642174554112"
+        );
+        assert_eq!(
+            BigInt::from(2).pow(100).to_string(),
+            "1267650600228229401496703205376"
+        );
+    }
+
+    #[test]
+    fn division() {
+        let n = big("-1267650600228229401496703205377");
+        let d = big("18446744073709551616");
+        let (q, r) = n.div_rem(&d).unwrap();
+        assert_eq!(q.to_string(), "-68719476736");
+        assert_eq!(r.to_string(), "-1");
+        let (q, r) = n.div_mod_floor(&d).unwrap();
+        assert_eq!(q.to_string(), "-68719476737");
+        assert_eq!(r.to_string(), "18446744073709551615");
+        assert!(n.div_rem(&BigInt::zero()).is_none());
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(BigInt::from(i64::MAX).to_i64(), Some(i64::MAX));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+        assert_eq!(
+            big("-18446744073709551616").to_f64(),
+            -18446744073709551616.0
+        );
+        assert_eq!(
+            BigInt::from_f64(-1e20).unwrap().to_string(),
+            "-100000000000000000000"
+        );
+        assert_eq!(BigInt::from_f64(-0.0), Some(BigInt::zero()));
+        assert_eq!(BigInt::from_f64(0.5), None);
+        assert!(big("-5") < big("3"));
+        assert!(big("-50000000000000000000") < big("-5"));
+    }
+}
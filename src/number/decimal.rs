@@ -0,0 +1,266 @@
+use std::{cmp::Ordering, fmt};
+
+use super::BigInt;
+
+/// How [`Decimal::div`] and [`Decimal::round`] settle a result that doesn't fit the
+/// requested scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Round to nearest, ties to the even neighbour (banker's rounding).
+    #[default]
+    HalfEven,
+    /// Round to nearest, ties away from zero.
+    HalfUp,
+    /// Round to nearest, ties towards zero.
+    HalfDown,
+    /// Away from zero.
+    Up,
+    /// Towards zero.
+    Down,
+    /// Towards positive infinity.
+    Ceiling,
+    /// Towards negative infinity.
+    Floor,
+}
+
+/// Exact decimal number `unscaled / 10^scale`.
+///
+/// The scale is kept as written, so `12.50d` prints as `12.50`; equality of two values
+/// with different scales goes through [`Decimal::cmp`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decimal {
+    unscaled: BigInt,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(unscaled: BigInt, scale: u32) -> Self {
+        Self { unscaled, scale }
+    }
+
+    /// Parse the digits of a decimal literal, e.g. `12.50` or `-3`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.split_once('.') {
+            Some((whole, fraction)) => {
+                if fraction.is_empty() || fraction.starts_with(['-', '+']) {
+                    return None;
+                }
+                let unscaled = BigInt::parse(&format!("{}{}", whole, fraction))?;
+                Some(Self::new(unscaled, fraction.len() as u32))
+            }
+            None => Some(Self::new(BigInt::parse(text)?, 0)),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// Digits after the point.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// `self` to the power of `exp`, exactly, by squaring.
+    pub fn pow(&self, exp: u32) -> Self {
+        Self::new(self.unscaled.pow(exp), self.scale * exp)
+    }
+
+    pub fn neg(&self) -> Self {
+        Self::new(-&self.unscaled, self.scale)
+    }
+
+    pub fn add(&self, rhs: &Decimal) -> Self {
+        let (left, right, scale) = self.align(rhs);
+        Self::new(&left + &right, scale)
+    }
+
+    pub fn sub(&self, rhs: &Decimal) -> Self {
+        self.add(&rhs.neg())
+    }
+
+    pub fn mul(&self, rhs: &Decimal) -> Self {
+        Self::new(&self.unscaled * &rhs.unscaled, self.scale + rhs.scale)
+    }
+
+    /// `self / rhs` rounded to `scale` digits after the point, then stripped of trailing
+    /// zeros down to the larger of the operands' scales. `None` when `rhs` is zero.
+    pub fn div(&self, rhs: &Decimal, scale: u32, mode: RoundingMode) -> Option<Self> {
+        // self / rhs = (a / 10^sa) / (b / 10^sb) = a * 10^sb / (b * 10^sa)
+        let numerator = &(&self.unscaled * &BigInt::pow10(rhs.scale)) * &BigInt::pow10(scale);
+        let denominator = &rhs.unscaled * &BigInt::pow10(self.scale);
+        let quotient = round_quotient(&numerator, &denominator, mode)?;
+
+        Some(Self::new(quotient, scale).trim(self.scale.max(rhs.scale)))
+    }
+
+    /// The largest integer not greater than `self / rhs`, `None` when `rhs` is zero.
+    pub fn div_floor(&self, rhs: &Decimal) -> Option<BigInt> {
+        let (left, right, _) = self.align(rhs);
+        Some(left.div_mod_floor(&right)?.0)
+    }
+
+    /// Round to `scale` digits after the point.
+    pub fn round(&self, scale: u32, mode: RoundingMode) -> Self {
+        if scale >= self.scale {
+            return self.rescale(scale);
+        }
+        let divisor = BigInt::pow10(self.scale - scale);
+        let unscaled = round_quotient(&self.unscaled, &divisor, mode).unwrap_or_default();
+        Self::new(unscaled, scale)
+    }
+
+    /// The value as an integer, if it has no fractional part.
+    pub fn to_integer(&self) -> Option<BigInt> {
+        let (quot, rem) = self.unscaled.div_rem(&BigInt::pow10(self.scale))?;
+        rem.is_zero().then_some(quot)
+    }
+
+    fn rescale(&self, scale: u32) -> Self {
+        debug_assert!(scale >= self.scale);
+        Self::new(&self.unscaled * &BigInt::pow10(scale - self.scale), scale)
+    }
+
+    /// Bring both values to the same scale, returning their unscaled values and it.
+    fn align(&self, rhs: &Decimal) -> (BigInt, BigInt, u32) {
+        let scale = self.scale.max(rhs.scale);
+        (
+            self.rescale(scale).unscaled,
+            rhs.rescale(scale).unscaled,
+            scale,
+        )
+    }
+
+    /// Drop trailing fractional zeros while the scale is above `min_scale`.
+    fn trim(mut self, min_scale: u32) -> Self {
+        let ten = BigInt::from(10);
+        while self.scale > min_scale {
+            match self.unscaled.div_rem(&ten) {
+                Some((quot, rem)) if rem.is_zero() => {
+                    self.unscaled = quot;
+                    self.scale -= 1;
+                }
+                _ => break,
+            }
+        }
+        self
+    }
+}
+
+impl From<BigInt> for Decimal {
+    fn from(value: BigInt) -> Self {
+        Self::new(value, 0)
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (left, right, _) = self.align(other);
+        left.cmp(&right)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.unscaled.abs().to_string();
+        let scale = self.scale as usize;
+        let sign = if self.unscaled.is_negative() { "-" } else { "" };
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
+
+/// `numerator / denominator` rounded to an integer according to `mode`.
+fn round_quotient(numerator: &BigInt, denominator: &BigInt, mode: RoundingMode) -> Option<BigInt> {
+    let (quot, rem) = numerator.div_rem(denominator)?;
+    if rem.is_zero() {
+        return Some(quot);
+    }
+
+    let negative = numerator.is_negative() != denominator.is_negative();
+    // how the discarded part compares to one half
+    let half = (&rem.abs() * &BigInt::from(2)).cmp(&denominator.abs());
+    let away_from_zero = match mode {
+        RoundingMode::Up => true,
+        RoundingMode::Down => false,
+        RoundingMode::Ceiling => !negative,
+        RoundingMode::Floor => negative,
+        RoundingMode::HalfUp => half != Ordering::Less,
+        RoundingMode::HalfDown => half == Ordering::Greater,
+        RoundingMode::HalfEven => match half {
+            Ordering::Less => false,
+            Ordering::Greater => true,
+            Ordering::Equal => !quot
+                .div_mod_floor(&BigInt::from(2))
+                .is_some_and(|(_, parity)| parity.is_zero()),
+        },
+    };
+
+    if !away_from_zero {
+        return Some(quot);
+    }
+    let step = BigInt::from(if negative { -1 } else { 1 });
+    Some(&quot + &step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(text: &str) -> Decimal {
+        Decimal::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for text in ["12.50", "-0.05", "3", "100.000"] {
+            assert_eq!(dec(text).to_string(), text);
+        }
+        assert_eq!(dec("0.1").add(&dec("0.2")).to_string(), "0.3");
+        assert_eq!(dec("1.5").mul(&dec("-0.25")).to_string(), "-0.375");
+        assert_eq!(dec("1.00").cmp(&dec("1")), Ordering::Equal);
+    }
+
+    #[test]
+    fn division() {
+        let third = dec("1").div(&dec("3"), 5, RoundingMode::HalfEven).unwrap();
+        assert_eq!(third.to_string(), "0.33333");
+        let quarter = dec("10.00")
+            .div(&dec("4"), 20, RoundingMode::HalfEven)
+            .unwrap();
+        assert_eq!(quarter.to_string(), "2.50");
+        assert!(dec("1").div(&dec("0.0"), 2, RoundingMode::Up).is_none());
+        assert_eq!(dec("-7.5").div_floor(&dec("2")).unwrap().to_string(), "-4");
+    }
+
+    #[test]
+    fn rounding_modes() {
+        let cases = [
+            (RoundingMode::HalfEven, ["2", "2", "-2", "3"]),
+            (RoundingMode::HalfUp, ["3", "2", "-3", "3"]),
+            (RoundingMode::HalfDown, ["2", "2", "-2", "3"]),
+            (RoundingMode::Up, ["3", "3", "-3", "3"]),
+            (RoundingMode::Down, ["2", "2", "-2", "2"]),
+            (RoundingMode::Ceiling, ["3", "3", "-2", "3"]),
+            (RoundingMode::Floor, ["2", "2", "-3", "2"]),
+        ];
+        for (mode, expected) in cases {
+            let rounded = ["2.5", "2.1", "-2.5", "2.7"].map(|n| dec(n).round(0, mode).to_string());
+            assert_eq!(rounded, expected, "{:?}", mode);
+        }
+        assert_eq!(dec("3.5").round(0, RoundingMode::HalfEven).to_string(), "4");
+    }
+}
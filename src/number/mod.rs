@@ -0,0 +1,7 @@
+//! Exact numeric types backing the `BigInt` and `Decimal` runtime values.
+
+pub use bigint::BigInt;
+pub use decimal::{Decimal, RoundingMode};
+
+mod bigint;
+mod decimal;
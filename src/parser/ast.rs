@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use crate::lexer::{LiteralTypes, Token};
 
 #[derive(Clone, Debug, PartialEq)]
@@ -10,6 +12,19 @@ pub enum Expr {
     Variable(Variable),
     Assign(Assign),
     Logical(Logical),
+    Match(Match),
+    Conditional(Conditional),
+    Compound(Compound),
+    List(List),
+    Tuple(Tuple),
+    Map(Map),
+    Range(Range),
+    Index(Index),
+    Slice(Slice),
+    IndexSet(IndexSet),
+    Call(Call),
+    Get(Get),
+    Destructure(Destructure),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -55,6 +70,170 @@ pub struct Logical {
     pub right: Box<Expr>,
 }
 
+/// `[a, b, c]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct List {
+    pub bracket: Token,
+    pub elements: Vec<Expr>,
+}
+
+/// `(a, b)`, `(a,)` or `()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tuple {
+    pub paren: Token,
+    pub elements: Vec<Expr>,
+}
+
+/// What a `var` declaration or a destructuring assignment binds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Name(Token),
+    /// `(a, b)` or `[a, b, ...rest]`, matching any tuple or list. `rest` collects the
+    /// elements after `items` into a value of the same kind as the one destructured.
+    Sequence {
+        open: Token,
+        items: Vec<Target>,
+        rest: Option<Token>,
+    },
+}
+
+/// `(a, b) = value` or `[a, ...rest] = value`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Destructure {
+    pub target: Target,
+    pub value: Box<Expr>,
+}
+
+/// `{key: value, ...}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Map {
+    pub brace: Token,
+    pub entries: Vec<(Expr, Expr)>,
+}
+
+/// `start..end`, `start..=end`, optionally followed by `step n`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub start: Box<Expr>,
+    pub op: Token,
+    pub end: Box<Expr>,
+    pub step: Option<Box<Expr>>,
+}
+
+/// `object[index]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+/// `object[start:end]`, either bound may be left out
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slice {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub start: Option<Box<Expr>>,
+    pub end: Option<Box<Expr>>,
+}
+
+/// `object[index] = value`
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexSet {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub callee: Box<Expr>,
+    pub paren: Token,
+    pub arguments: Vec<Expr>,
+}
+
+/// `object.name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Get {
+    pub object: Box<Expr>,
+    pub name: Token,
+}
+
+/// `target op= value`, `++target`, `target++` and friends.
+///
+/// `op` is the arithmetic operator applied to the current value of `target` and `value`;
+/// a postfix update evaluates to the value `target` held before the update.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Compound {
+    pub target: Box<Expr>,
+    pub op: Token,
+    pub value: Box<Expr>,
+    pub postfix: bool,
+}
+
+/// `condition ? then_branch : else_branch`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conditional {
+    pub condition: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Box<Expr>,
+}
+
+/// A single arm pattern of a `match`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Literal(LiteralTypes),
+    Range {
+        start: LiteralTypes,
+        end: LiteralTypes,
+        inclusive: bool,
+    },
+    Wildcard,
+}
+
+impl Pattern {
+    pub fn matches(&self, value: &LiteralTypes) -> bool {
+        match (self, value) {
+            (Pattern::Wildcard, _) => true,
+            (Pattern::Literal(literal), value) => literal.equal(value),
+            (
+                Pattern::Range {
+                    start,
+                    end,
+                    inclusive,
+                },
+                value,
+            ) => {
+                let above = start.compare(value).is_some_and(Ordering::is_le);
+                let below = match value.compare(end) {
+                    Some(Ordering::Less) => true,
+                    Some(Ordering::Equal) => *inclusive,
+                    _ => false,
+                };
+                above && below
+            }
+        }
+    }
+}
+
+/// `pattern ( "|" pattern )* ( "if" guard )? "=>" body`
+///
+/// Shared by the statement form (`B = Stmt`) and the expression form (`B = Expr`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm<B> {
+    pub patterns: Vec<Pattern>,
+    pub guard: Option<Expr>,
+    pub body: B,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub keyword: Token,
+    pub subject: Box<Expr>,
+    pub arms: Vec<MatchArm<Expr>>,
+}
+
 pub trait Visitor<T> {
     fn visit_binary(&mut self, expr: &Binary) -> T;
     fn visit_grouping(&mut self, expr: &Grouping) -> T;
@@ -64,6 +243,19 @@ pub trait Visitor<T> {
     fn visit_variable(&mut self, expr: &Variable) -> T;
     fn visit_assign_expr(&mut self, expr: &Assign) -> T;
     fn visit_logical_expr(&mut self, expr: &Logical) -> T;
+    fn visit_match_expr(&mut self, expr: &Match) -> T;
+    fn visit_conditional_expr(&mut self, expr: &Conditional) -> T;
+    fn visit_compound_expr(&mut self, expr: &Compound) -> T;
+    fn visit_list_expr(&mut self, expr: &List) -> T;
+    fn visit_tuple_expr(&mut self, expr: &Tuple) -> T;
+    fn visit_map_expr(&mut self, expr: &Map) -> T;
+    fn visit_range_expr(&mut self, expr: &Range) -> T;
+    fn visit_index_expr(&mut self, expr: &Index) -> T;
+    fn visit_slice_expr(&mut self, expr: &Slice) -> T;
+    fn visit_index_set_expr(&mut self, expr: &IndexSet) -> T;
+    fn visit_call_expr(&mut self, expr: &Call) -> T;
+    fn visit_get_expr(&mut self, expr: &Get) -> T;
+    fn visit_destructure_expr(&mut self, expr: &Destructure) -> T;
 }
 
 impl Expr {
@@ -77,6 +269,19 @@ impl Expr {
             Expr::Variable(var) => visitor.visit_variable(var),
             Expr::Assign(a) => visitor.visit_assign_expr(a),
             Expr::Logical(l) => visitor.visit_logical_expr(l),
+            Expr::Match(m) => visitor.visit_match_expr(m),
+            Expr::Conditional(c) => visitor.visit_conditional_expr(c),
+            Expr::Compound(c) => visitor.visit_compound_expr(c),
+            Expr::List(l) => visitor.visit_list_expr(l),
+            Expr::Tuple(t) => visitor.visit_tuple_expr(t),
+            Expr::Map(m) => visitor.visit_map_expr(m),
+            Expr::Range(r) => visitor.visit_range_expr(r),
+            Expr::Index(i) => visitor.visit_index_expr(i),
+            Expr::Slice(s) => visitor.visit_slice_expr(s),
+            Expr::IndexSet(i) => visitor.visit_index_set_expr(i),
+            Expr::Call(c) => visitor.visit_call_expr(c),
+            Expr::Get(g) => visitor.visit_get_expr(g),
+            Expr::Destructure(d) => visitor.visit_destructure_expr(d),
         }
     }
 }
@@ -0,0 +1,119 @@
+//! Values that can be called with `f(args)`.
+
+use std::{fmt, ops::RangeInclusive, rc::Rc};
+
+use crate::lexer::LiteralTypes;
+
+use super::{exception::ErrorKind, inter::Interpreter};
+
+#[derive(Debug, Clone)]
+pub enum Callable {
+    Native(Rc<Native>),
+}
+
+type NativeFn = dyn Fn(&mut Interpreter, Vec<LiteralTypes>) -> Result<LiteralTypes, NativeError>;
+
+/// A function implemented in Rust.
+pub struct Native {
+    pub name: String,
+    /// How many arguments it accepts; checked before `function` runs.
+    pub arity: RangeInclusive<usize>,
+    pub function: Box<NativeFn>,
+}
+
+/// Why a native call failed. The interpreter attributes errors to the call site.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NativeError {
+    Error(ErrorKind, String),
+    /// `exit(code)`: unwind everything and end the program.
+    Exit(i32),
+}
+
+impl NativeError {
+    pub fn type_error(message: impl Into<String>) -> Self {
+        NativeError::Error(ErrorKind::Type, message.into())
+    }
+}
+
+/// Plain messages, such as those from the list and map methods, are runtime errors.
+impl From<String> for NativeError {
+    fn from(message: String) -> Self {
+        NativeError::Error(ErrorKind::Runtime, message)
+    }
+}
+
+impl Callable {
+    /// Wrap a Rust function as a callable value.
+    pub fn native(
+        name: &str,
+        arity: RangeInclusive<usize>,
+        function: impl Fn(&mut Interpreter, Vec<LiteralTypes>) -> Result<LiteralTypes, NativeError>
+            + 'static,
+    ) -> LiteralTypes {
+        LiteralTypes::Callable(Callable::Native(Rc::new(Native {
+            name: name.to_string(),
+            arity,
+            function: Box::new(function),
+        })))
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Callable::Native(native) => &native.name,
+        }
+    }
+
+    pub fn check_arity(&self, count: usize) -> Result<(), String> {
+        match self {
+            Callable::Native(native) => check_arity(&native.arity, count),
+        }
+    }
+
+    pub fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<LiteralTypes>,
+    ) -> Result<LiteralTypes, NativeError> {
+        match self {
+            Callable::Native(native) => (native.function)(interpreter, arguments),
+        }
+    }
+}
+
+/// Reject a call with `count` arguments, e.g. "Expected 1 to 2 arguments but got 3."
+pub fn check_arity(arity: &RangeInclusive<usize>, count: usize) -> Result<(), String> {
+    if arity.contains(&count) {
+        return Ok(());
+    }
+    let expected = match (arity.start(), arity.end()) {
+        (min, max) if min == max => min.to_string(),
+        (min, &usize::MAX) => format!("at least {}", min),
+        (min, max) => format!("{} to {}", min, max),
+    };
+    Err(format!(
+        "Expected {} arguments but got {}.",
+        expected, count
+    ))
+}
+
+impl PartialEq for Callable {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Callable::Native(left), Callable::Native(right)) => Rc::ptr_eq(left, right),
+        }
+    }
+}
+
+impl fmt::Display for Callable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Callable::Native(native) => write!(f, "<native fn {}>", native.name),
+        }
+    }
+}
+
+impl fmt::Debug for Native {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Native({})", self.name)
+    }
+}
@@ -0,0 +1,139 @@
+//! Enum types declared with `enum Name { ... }` and the variant values they create.
+
+use std::{fmt, rc::Rc};
+
+use crate::lexer::LiteralTypes;
+
+#[derive(Debug, PartialEq)]
+pub struct EnumType {
+    pub name: String,
+    pub variants: Vec<VariantDef>,
+}
+
+/// A variant as declared: `Red`, or `Rect(w, h)` with payload fields.
+#[derive(Debug, PartialEq)]
+pub struct VariantDef {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+/// A value of an enum type, such as `Color.Red` or `Shape.Rect(2, 3)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub ty: Rc<EnumType>,
+    pub tag: usize,
+    pub payload: Vec<LiteralTypes>,
+}
+
+impl EnumType {
+    pub fn variant(&self, name: &str) -> Option<usize> {
+        self.variants
+            .iter()
+            .position(|variant| variant.name == name)
+    }
+
+    /// `Enum.name`: a plain variant itself. Variants with a payload must be called.
+    pub fn get(self: &Rc<Self>, name: &str) -> Result<LiteralTypes, String> {
+        let tag = self.lookup(name)?;
+        let fields = self.variants[tag].fields.len();
+        if fields > 0 {
+            return Err(format!(
+                "Variant '{}' takes {} payload values; call it like {}.{}(...).",
+                name, fields, self.name, name
+            ));
+        }
+        Ok(self.construct(tag, Vec::new()))
+    }
+
+    /// `Enum.name(args)`: a variant carrying `args` as its payload.
+    pub fn call(
+        self: &Rc<Self>,
+        name: &str,
+        args: Vec<LiteralTypes>,
+    ) -> Result<LiteralTypes, String> {
+        let tag = self.lookup(name)?;
+        let arity = self.variants[tag].fields.len();
+        if arity == 0 {
+            return Err(format!(
+                "Variant '{}' has no payload; use {}.{} without calling it.",
+                name, self.name, name
+            ));
+        }
+        if args.len() != arity {
+            return Err(format!(
+                "Expected {} arguments but got {}.",
+                arity,
+                args.len()
+            ));
+        }
+        Ok(self.construct(tag, args))
+    }
+
+    fn lookup(&self, name: &str) -> Result<usize, String> {
+        self.variant(name)
+            .ok_or_else(|| format!("Undefined variant '{}' on enum {}.", name, self.name))
+    }
+
+    fn construct(self: &Rc<Self>, tag: usize, payload: Vec<LiteralTypes>) -> LiteralTypes {
+        LiteralTypes::Variant(Rc::new(Variant {
+            ty: self.clone(),
+            tag,
+            payload,
+        }))
+    }
+}
+
+impl Variant {
+    pub fn name(&self) -> &str {
+        &self.ty.variants[self.tag].name
+    }
+
+    /// `variant.tag` is the variant's name; `variant.field` reads the payload.
+    pub fn get(&self, property: &str) -> Result<LiteralTypes, String> {
+        if property == "tag" {
+            return Ok(LiteralTypes::String(self.name().to_string()));
+        }
+        let fields = &self.ty.variants[self.tag].fields;
+        match fields.iter().position(|field| field == property) {
+            Some(at) => Ok(self.payload[at].clone()),
+            None => Err(format!(
+                "Undefined property '{}' on {}.{}.",
+                property,
+                self.ty.name,
+                self.name()
+            )),
+        }
+    }
+
+    /// Variants are equal when they are the same variant of the same declaration and
+    /// their payloads are equal by `equal`.
+    pub fn equal(
+        &self,
+        rhs: &Variant,
+        mut equal: impl FnMut(&LiteralTypes, &LiteralTypes) -> bool,
+    ) -> bool {
+        Rc::ptr_eq(&self.ty, &rhs.ty)
+            && self.tag == rhs.tag
+            && self
+                .payload
+                .iter()
+                .zip(rhs.payload.iter())
+                .all(|(l, r)| equal(l, r))
+    }
+
+    /// `Shape.Rect(2, 3)`, with each payload value written by `stringify`.
+    pub fn render(&self, stringify: impl FnMut(&LiteralTypes) -> String) -> String {
+        let name = format!("{}.{}", self.ty.name, self.name());
+        if self.payload.is_empty() {
+            return name;
+        }
+        let payload: Vec<String> = self.payload.iter().map(stringify).collect();
+        format!("{}({})", name, payload.join(", "))
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(LiteralTypes::stringify))
+    }
+}
@@ -1,4 +1,8 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
 use crate::lexer::{LiteralTypes, Token};
 
@@ -6,6 +10,14 @@ use crate::lexer::{LiteralTypes, Token};
 pub struct Environment {
     enclosing: Option<Rc<RefCell<Environment>>>,
     values: HashMap<String, LiteralTypes>,
+    /// Names declared with `const` in this scope.
+    constants: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnvError {
+    Undefined,
+    Constant,
 }
 
 impl Environment {
@@ -13,30 +25,59 @@ impl Environment {
         Self {
             enclosing: None,
             values: HashMap::new(),
+            constants: HashSet::new(),
         }
     }
     pub fn new_with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
         Self {
             enclosing: Some(enclosing),
             values: HashMap::new(),
+            constants: HashSet::new(),
         }
     }
     pub fn define(&mut self, key: String, value: LiteralTypes) {
         self.values.insert(key, value);
     }
 
+    /// Define `key` for a `var` or `const` declaration. A constant can't be declared
+    /// again in the same scope; inner scopes may still shadow it.
+    pub fn declare(
+        &mut self,
+        key: String,
+        value: LiteralTypes,
+        constant: bool,
+    ) -> Result<(), EnvError> {
+        if self.constants.contains(&key) {
+            return Err(EnvError::Constant);
+        }
+        if constant {
+            self.constants.insert(key.clone());
+        }
+        self.define(key, value);
+        Ok(())
+    }
+
     pub fn get(&self, token: &Token) -> Option<LiteralTypes> {
         let key = &token.lexeme;
         if self.values.contains_key(key) {
-            return self.values.get(key).map(Clone::clone);
+            return self.values.get(key).cloned();
         } else if let Some(enclosing) = &self.enclosing {
             return enclosing.borrow().get(token);
         }
 
         None
     }
-    pub fn assign(&mut self, name: &Token, value: LiteralTypes) -> Result<(), ()> {
+
+    /// The value of `token` in this scope alone, ignoring enclosing ones.
+    pub fn get_local(&self, token: &Token) -> Option<LiteralTypes> {
+        self.values.get(&token.lexeme).cloned()
+    }
+
+    pub fn assign(&mut self, name: &Token, value: LiteralTypes) -> Result<(), EnvError> {
         if self.values.contains_key(&name.lexeme) {
+            if self.constants.contains(&name.lexeme) {
+                return Err(EnvError::Constant);
+            }
             self.values.insert(name.lexeme.clone(), value);
             return Ok(());
         } else if let Some(enclosing) = &self.enclosing {
@@ -44,6 +85,41 @@ impl Environment {
             return Ok(());
         }
 
-        Err(())
+        Err(EnvError::Undefined)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::TokenType;
+
+    #[test]
+    fn constants() {
+        let name = Token::new(TokenType::Identifier, "a".into(), LiteralTypes::Nil, 1);
+        let global = Rc::new(RefCell::new(Environment::new()));
+        global
+            .borrow_mut()
+            .declare("a".into(), LiteralTypes::Int(1), true)
+            .unwrap();
+
+        let mut inner = Environment::new_with_enclosing(global.clone());
+        assert_eq!(
+            inner.assign(&name, LiteralTypes::Int(2)),
+            Err(EnvError::Constant)
+        );
+        assert_eq!(
+            global
+                .borrow_mut()
+                .declare("a".into(), LiteralTypes::Int(2), false),
+            Err(EnvError::Constant)
+        );
+
+        // shadowing in an inner scope is a new binding
+        inner
+            .declare("a".into(), LiteralTypes::Int(3), false)
+            .unwrap();
+        inner.assign(&name, LiteralTypes::Int(4)).unwrap();
+        assert_eq!(global.borrow().get(&name), Some(LiteralTypes::Int(1)));
     }
 }
@@ -0,0 +1,81 @@
+//! Errors as Lox values: what `catch (e)` binds and what `throw` raises.
+
+use std::fmt;
+
+use crate::lexer::LiteralTypes;
+
+/// The broad category of an error, exposed to scripts as `e.kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Raised with `throw`.
+    Error,
+    /// An operation applied to a value of the wrong type.
+    Type,
+    /// An undefined variable, or a write to a constant.
+    Name,
+    /// A list or tuple index out of range.
+    Index,
+    /// A map key that isn't present.
+    Key,
+    /// Division by zero, overflow and other numeric domain errors.
+    Arithmetic,
+    /// Text that doesn't parse, such as malformed JSON.
+    Parse,
+    /// A file access outside what the host allows.
+    Permission,
+    /// A file that can't be read, written or listed.
+    Io,
+    /// A failed `assert()`.
+    Assertion,
+    /// A module that can't be found, read, parsed or that imports itself.
+    Import,
+    /// Anything else the interpreter rejects.
+    Runtime,
+}
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Error => "Error",
+            ErrorKind::Type => "TypeError",
+            ErrorKind::Name => "NameError",
+            ErrorKind::Index => "IndexError",
+            ErrorKind::Key => "KeyError",
+            ErrorKind::Arithmetic => "ArithmeticError",
+            ErrorKind::Import => "ImportError",
+            ErrorKind::Assertion => "AssertionError",
+            ErrorKind::Parse => "ParseError",
+            ErrorKind::Permission => "PermissionError",
+            ErrorKind::Io => "IOError",
+            ErrorKind::Runtime => "RuntimeError",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ErrorObject {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub line: usize,
+    /// What was passed to `throw`; `nil` for errors raised by the interpreter.
+    pub value: LiteralTypes,
+}
+
+impl ErrorObject {
+    /// `e.message`, `e.line`, `e.kind` and `e.value`.
+    pub fn get(&self, property: &str) -> Result<LiteralTypes, String> {
+        match property {
+            "message" => Ok(LiteralTypes::String(self.message.clone())),
+            "line" => Ok(LiteralTypes::Int(self.line as i64)),
+            "kind" => Ok(LiteralTypes::String(self.kind.name().to_string())),
+            "value" => Ok(self.value.clone()),
+            _ => Err(format!("Undefined property '{}' on error.", property)),
+        }
+    }
+}
+
+impl fmt::Display for ErrorObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind.name(), self.message)
+    }
+}
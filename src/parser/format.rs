@@ -0,0 +1,517 @@
+//! `format(template, args...)`, after Python's `str.format` and Rust's `format!`.
+//!
+//! `{}` takes the next argument, `{1}` a given one and `{name}` a key of the map passed
+//! as the last argument. After a `:` comes `[[fill]align][+][#][0][width][.precision][style]`:
+//! `<`, `>` or `^` to align, then a style of `d`, `f`, `e`, `E`, `x`, `X`, `o`, `b` or
+//! `s`. `{{` and `}}` are literal braces. Positions in errors count chars of the template.
+
+use crate::{
+    lexer::LiteralTypes,
+    number::{BigInt, RoundingMode},
+};
+
+use super::{callable::NativeError, exception::ErrorKind};
+
+/// Widths and precisions past this are surely mistakes, and would only eat memory.
+const MAX_WIDTH: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Debug, Default)]
+struct Spec {
+    fill: Option<char>,
+    align: Option<Align>,
+    plus: bool,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    style: Option<char>,
+}
+
+pub fn format(template: &str, args: &[LiteralTypes]) -> Result<String, NativeError> {
+    let chars: Vec<char> = template.chars().collect();
+    let mut out = String::new();
+    let mut next_arg = 0;
+    let mut at = 0;
+
+    while let Some(&char) = chars.get(at) {
+        match (char, chars.get(at + 1)) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                out.push(char);
+                at += 2;
+            }
+            ('}', _) => return Err(syntax(format!("Unmatched '}}' at position {}.", at))),
+            ('{', _) => {
+                let Some(len) = chars[at..].iter().position(|c| *c == '}') else {
+                    return Err(syntax(format!("Unmatched '{{' at position {}.", at)));
+                };
+                let inside = &chars[at + 1..at + len];
+                let colon = inside.iter().position(|c| *c == ':');
+                let name: String = inside[..colon.unwrap_or(inside.len())].iter().collect();
+
+                let value = argument(&name, at, args, &mut next_arg)?;
+                let spec = match colon {
+                    Some(colon) => parse_spec(&inside[colon + 1..], at + colon + 2)?,
+                    None => Spec::default(),
+                };
+                out.push_str(&render(&value, &spec, at)?);
+                at += len + 1;
+            }
+            _ => {
+                out.push(char);
+                at += 1;
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn syntax(message: String) -> NativeError {
+    NativeError::Error(ErrorKind::Parse, message)
+}
+
+/// What the placeholder at `position` stands for: the next argument, one by index, or
+/// one by key from the trailing map.
+fn argument(
+    name: &str,
+    position: usize,
+    args: &[LiteralTypes],
+    next_arg: &mut usize,
+) -> Result<LiteralTypes, NativeError> {
+    let index = if name.is_empty() {
+        *next_arg += 1;
+        *next_arg - 1
+    } else if name.bytes().all(|b| b.is_ascii_digit()) {
+        name.parse().unwrap_or(usize::MAX)
+    } else if name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+    {
+        let Some(LiteralTypes::Map(map)) = args.last() else {
+            return Err(NativeError::Error(
+                ErrorKind::Key,
+                format!(
+                    "The placeholder '{}' at position {} needs a map of named arguments last.",
+                    name, position
+                ),
+            ));
+        };
+        let key = LiteralTypes::String(name.to_string());
+        return match map.borrow().get(&key) {
+            Ok(Some(value)) => Ok(value),
+            _ => Err(NativeError::Error(
+                ErrorKind::Key,
+                format!("No argument named '{}' at position {}.", name, position),
+            )),
+        };
+    } else {
+        return Err(syntax(format!(
+            "Invalid argument name '{}' at position {}.",
+            name,
+            position + 1
+        )));
+    };
+
+    args.get(index).cloned().ok_or_else(|| {
+        NativeError::Error(
+            ErrorKind::Index,
+            format!(
+                "The placeholder at position {} wants argument {}, but only {} were given.",
+                position,
+                index,
+                args.len()
+            ),
+        )
+    })
+}
+
+/// `spec` is what follows the colon, which starts at `start` in the template.
+fn parse_spec(spec: &[char], start: usize) -> Result<Spec, NativeError> {
+    let align = |char: Option<&char>| match char {
+        Some('<') => Some(Align::Left),
+        Some('>') => Some(Align::Right),
+        Some('^') => Some(Align::Center),
+        _ => None,
+    };
+    let mut result = Spec::default();
+    let mut at = 0;
+
+    if let Some(aligned) = align(spec.get(1)) {
+        result.fill = Some(spec[0]);
+        result.align = Some(aligned);
+        at = 2;
+    } else if let Some(aligned) = align(spec.first()) {
+        result.align = Some(aligned);
+        at = 1;
+    }
+    if spec.get(at) == Some(&'+') {
+        result.plus = true;
+        at += 1;
+    }
+    if spec.get(at) == Some(&'#') {
+        result.alternate = true;
+        at += 1;
+    }
+    if spec.get(at) == Some(&'0') {
+        result.zero = true;
+        at += 1;
+    }
+    result.width = number(spec, &mut at, start, "Width")?.unwrap_or(0);
+    if spec.get(at) == Some(&'.') {
+        at += 1;
+        let Some(precision) = number(spec, &mut at, start, "Precision")? else {
+            return Err(syntax(format!(
+                "Expected a precision after '.' at position {}.",
+                start + at
+            )));
+        };
+        result.precision = Some(precision);
+    }
+    if let Some(&style) = spec.get(at) {
+        if !"dfeExXobs".contains(style) {
+            return Err(syntax(format!(
+                "Unknown format style '{}' at position {}.",
+                style,
+                start + at
+            )));
+        }
+        result.style = Some(style);
+        at += 1;
+    }
+    if let Some(extra) = spec.get(at) {
+        return Err(syntax(format!(
+            "Unexpected '{}' in format spec at position {}.",
+            extra,
+            start + at
+        )));
+    }
+    Ok(result)
+}
+
+fn number(
+    spec: &[char],
+    at: &mut usize,
+    start: usize,
+    what: &str,
+) -> Result<Option<usize>, NativeError> {
+    let begin = *at;
+    while spec.get(*at).is_some_and(char::is_ascii_digit) {
+        *at += 1;
+    }
+    if *at == begin {
+        return Ok(None);
+    }
+    let digits: String = spec[begin..*at].iter().collect();
+    match digits.parse() {
+        Ok(value) if value <= MAX_WIDTH => Ok(Some(value)),
+        _ => Err(syntax(format!(
+            "{} {} is above {} at position {}.",
+            what,
+            digits,
+            MAX_WIDTH,
+            start + begin
+        ))),
+    }
+}
+
+/// Format one value per `spec`, for the placeholder at `position`.
+fn render(value: &LiteralTypes, spec: &Spec, position: usize) -> Result<String, NativeError> {
+    let type_error = |wanted: &str| {
+        NativeError::type_error(format!(
+            "The placeholder at position {} wants {}, got {}.",
+            position,
+            wanted,
+            value.type_name()
+        ))
+    };
+
+    let numeric = match (spec.style, value) {
+        (Some('s'), _) => None,
+        (Some('d'), LiteralTypes::Int(_) | LiteralTypes::BigInt(_)) => Some(value.stringify()),
+        (Some('d'), _) => return Err(type_error("an int")),
+        (Some(style @ ('x' | 'X' | 'o' | 'b')), LiteralTypes::Int(int)) => {
+            let sign = if *int < 0 { "-" } else { "" };
+            Some(format!("{}{}", sign, radix(int.unsigned_abs(), style, 0)))
+        }
+        (Some(style @ ('x' | 'X' | 'o' | 'b')), LiteralTypes::BigInt(int)) => {
+            let sign = if int.is_negative() { "-" } else { "" };
+            Some(format!("{}{}", sign, big_radix(int, style)))
+        }
+        (Some('x' | 'X' | 'o' | 'b'), _) => return Err(type_error("an int")),
+        (Some(style @ ('e' | 'E')), _) => {
+            let num = value.as_f64().ok_or_else(|| type_error("a number"))?;
+            let text = scientific(num, spec.precision.unwrap_or(6));
+            Some(if style == 'E' {
+                text.to_uppercase()
+            } else {
+                text
+            })
+        }
+        (Some('f') | None, LiteralTypes::Decimal(decimal)) => {
+            let precision = spec.precision.or((spec.style == Some('f')).then_some(6));
+            Some(match precision {
+                Some(precision) => decimal
+                    .round(precision as u32, RoundingMode::default())
+                    .to_string(),
+                None => decimal.to_string(),
+            })
+        }
+        (Some('f'), _) => {
+            let num = value.as_f64().ok_or_else(|| type_error("a number"))?;
+            Some(format!("{:.*}", spec.precision.unwrap_or(6), num))
+        }
+        (None, _) if value.as_f64().is_some() => Some(match spec.precision {
+            Some(precision) => format!("{:.*}", precision, value.as_f64().unwrap_or_default()),
+            None => value.stringify(),
+        }),
+        _ => None,
+    };
+
+    let Some(numeric) = numeric else {
+        if spec.plus || spec.alternate {
+            return Err(type_error("a number for '+' or '#'"));
+        }
+        let text = value.to_text();
+        let text = match spec.precision {
+            Some(precision) => text.chars().take(precision).collect(),
+            None => text,
+        };
+        let fill = spec.fill.unwrap_or(if spec.zero { '0' } else { ' ' });
+        return Ok(pad(
+            &text,
+            fill,
+            spec.align.unwrap_or(Align::Left),
+            spec.width,
+        ));
+    };
+
+    let (negative, digits) = match numeric.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, numeric.as_str()),
+    };
+    let sign = match (negative, spec.plus) {
+        (true, _) => "-",
+        (false, true) => "+",
+        (false, false) => "",
+    };
+    let prefix = match spec.style {
+        Some('x' | 'X') if spec.alternate => "0x",
+        Some('o') if spec.alternate => "0o",
+        Some('b') if spec.alternate => "0b",
+        _ => "",
+    };
+    let head = format!("{}{}", sign, prefix);
+
+    if spec.zero && spec.align.is_none() {
+        // zeros go between the sign and the digits
+        let len = head.chars().count() + digits.chars().count();
+        let zeros = "0".repeat(spec.width.saturating_sub(len));
+        return Ok(format!("{}{}{}", head, zeros, digits));
+    }
+    let text = format!("{}{}", head, digits);
+    let fill = spec.fill.unwrap_or(' ');
+    Ok(pad(
+        &text,
+        fill,
+        spec.align.unwrap_or(Align::Right),
+        spec.width,
+    ))
+}
+
+/// `value` in the base of `style`, zero-padded to `width` digits.
+fn radix(value: u64, style: char, width: usize) -> String {
+    match style {
+        'x' => format!("{:0width$x}", value),
+        'X' => format!("{:0width$X}", value),
+        'o' => format!("{:0width$o}", value),
+        _ => format!("{:0width$b}", value),
+    }
+}
+
+/// The magnitude of a big int in the base of `style`, a chunk of digits that fits a
+/// `u64` at a time from the least significant end.
+fn big_radix(value: &BigInt, style: char) -> String {
+    let (base, chunk): (i64, u32) = match style {
+        'x' | 'X' => (16, 15),
+        'o' => (8, 20),
+        _ => (2, 62),
+    };
+    let divisor = BigInt::from(base.pow(chunk));
+    let mut rest = value.abs();
+    let mut chunks = Vec::new();
+    while !rest.is_zero() {
+        let (quot, rem) = rest.div_rem(&divisor).unwrap_or_default();
+        chunks.push(rem.to_i64().unwrap_or_default() as u64);
+        rest = quot;
+    }
+
+    let mut chunks = chunks.into_iter().rev();
+    let mut digits = radix(chunks.next().unwrap_or_default(), style, 0);
+    for chunk_value in chunks {
+        digits.push_str(&radix(chunk_value, style, chunk as usize));
+    }
+    digits
+}
+
+/// `1.50e+03`, with at least two exponent digits like C's `printf`.
+fn scientific(num: f64, precision: usize) -> String {
+    let text = format!("{:.*e}", precision, num);
+    let Some((mantissa, exponent)) = text.split_once('e') else {
+        // infinities and NaN
+        return text;
+    };
+    let exponent: i32 = exponent.parse().unwrap_or_default();
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exponent.abs())
+}
+
+fn pad(text: &str, fill: char, align: Align, width: usize) -> String {
+    let padding = width.saturating_sub(text.chars().count());
+    let (before, after) = match align {
+        Align::Left => (0, padding),
+        Align::Right => (padding, 0),
+        Align::Center => (padding / 2, padding - padding / 2),
+    };
+    let fill = |count| std::iter::repeat_n(fill, count).collect::<String>();
+    format!("{}{}{}", fill(before), text, fill(after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(template: &str, args: &[LiteralTypes]) -> String {
+        format(template, args).unwrap()
+    }
+
+    #[test]
+    fn aligns_and_pads() {
+        let pi = LiteralTypes::Number(std::f64::consts::PI);
+        let name = LiteralTypes::String("lox".into());
+        assert_eq!(
+            check("[{:>8.2}] [{}]", &[pi.clone(), name.clone()]),
+            "[    3.14] [lox]"
+        );
+        assert_eq!(
+            check("[{:6}] [{:6}]", &[name.clone(), LiteralTypes::Int(42)]),
+            "[lox   ] [    42]"
+        );
+        assert_eq!(
+            check("[{:*^7}] [{:.1}]", &[name.clone(), name]),
+            "[**lox**] [l]"
+        );
+        assert_eq!(
+            check("{:+08.3f}", &[LiteralTypes::Number(-1.5)]),
+            "-001.500"
+        );
+        assert_eq!(check("{:+}", &[LiteralTypes::Int(7)]), "+7");
+        assert_eq!(check("{{{}}}", &[LiteralTypes::Nil]), "{Nil}");
+    }
+
+    #[test]
+    fn number_styles() {
+        let int = LiteralTypes::Int(-255);
+        assert_eq!(
+            check(
+                "{:x} {:#X} {:#o} {:#010b}",
+                &[int.clone(), int.clone(), int, LiteralTypes::Int(5)]
+            ),
+            "-ff -0xFF -0o377 0b00000101"
+        );
+        assert_eq!(check("{:e}", &[LiteralTypes::Int(1500)]), "1.500000e+03");
+        assert_eq!(
+            check("{:.2E}", &[LiteralTypes::Number(0.000123)]),
+            "1.23E-04"
+        );
+        assert_eq!(check("{:f}", &[LiteralTypes::Number(0.5)]), "0.500000");
+        assert_eq!(check("{:.1}", &[LiteralTypes::Int(2)]), "2.0");
+
+        let big = |text: &str| LiteralTypes::BigInt(BigInt::parse(text).unwrap());
+        // 2 ** 70 and -(2 ** 64 + 255)
+        let two_70 = [big("1180591620717411303424")];
+        assert_eq!(check("{:x}", &two_70), "400000000000000000");
+        assert_eq!(check("{:o}", &two_70), "200000000000000000000000");
+        assert_eq!(check("{:b}", &two_70), format!("1{}", "0".repeat(70)));
+        let negative = [big("-18446744073709551871")];
+        assert_eq!(check("{:#X}", &negative), "-0x100000000000000FF");
+    }
+
+    #[test]
+    fn errors_say_where() {
+        let one = || vec![LiteralTypes::Int(1)];
+        for (template, args, kind, message) in [
+            (
+                "ab {",
+                one(),
+                ErrorKind::Parse,
+                "Unmatched '{' at position 3.",
+            ),
+            (
+                "ab }",
+                one(),
+                ErrorKind::Parse,
+                "Unmatched '}' at position 3.",
+            ),
+            (
+                "{:>8q}",
+                one(),
+                ErrorKind::Parse,
+                "Unknown format style 'q' at position 4.",
+            ),
+            (
+                "{:8.}",
+                one(),
+                ErrorKind::Parse,
+                "Expected a precision after '.' at position 4.",
+            ),
+            (
+                "{:xx}",
+                one(),
+                ErrorKind::Parse,
+                "Unexpected 'x' in format spec at position 3.",
+            ),
+            (
+                "{:99999}",
+                one(),
+                ErrorKind::Parse,
+                "Width 99999 is above 10000 at position 2.",
+            ),
+            (
+                "{a-b}",
+                one(),
+                ErrorKind::Parse,
+                "Invalid argument name 'a-b' at position 1.",
+            ),
+            (
+                "{} {}",
+                one(),
+                ErrorKind::Index,
+                "The placeholder at position 3 wants argument 1, but only 1 were given.",
+            ),
+            (
+                "{name}",
+                one(),
+                ErrorKind::Key,
+                "The placeholder 'name' at position 0 needs a map of named arguments last.",
+            ),
+            (
+                "{:s} {:x}",
+                vec![LiteralTypes::Int(1), LiteralTypes::Number(1.0)],
+                ErrorKind::Type,
+                "The placeholder at position 5 wants an int, got float.",
+            ),
+        ] {
+            match format(template, &args) {
+                Err(NativeError::Error(got, got_message)) => {
+                    assert_eq!((got, got_message.as_str()), (kind, message), "{}", template)
+                }
+                _ => panic!("{} should fail", template),
+            }
+        }
+    }
+}
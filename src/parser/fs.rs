@@ -0,0 +1,193 @@
+//! The `fs` module: file access confined to the directories the host allows.
+//!
+//! Every path is resolved, symlinks included, before it is checked against the
+//! [`Sandbox`], so `..` and links can't reach outside an allowed root. Links whose
+//! target doesn't exist can't be resolved, so they are refused outright.
+
+use std::{
+    cell::RefCell,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::lexer::LiteralTypes;
+
+use super::{
+    callable::{Callable, NativeError},
+    exception::ErrorKind,
+    inter::Interpreter,
+};
+
+/// What scripts may do under a root directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    ReadOnly,
+    ReadWrite,
+}
+
+/// The directories scripts may touch. Empty by default, which denies everything.
+#[derive(Debug, Clone, Default)]
+pub struct Sandbox {
+    roots: Vec<(PathBuf, Access)>,
+}
+
+impl Sandbox {
+    pub fn allow(&mut self, root: &Path, access: Access) {
+        self.roots.push((resolve(root), access));
+    }
+
+    /// Resolve `path` and make sure some root grants `access` to it.
+    pub fn check(&self, path: &str, access: Access) -> Result<PathBuf, NativeError> {
+        let resolved = resolve(Path::new(path));
+        let allowed = self.roots.iter().any(|(root, granted)| {
+            resolved.starts_with(root) && (access == Access::ReadOnly || *granted == access)
+        });
+        // `resolve` follows every link that leads somewhere, so any link left is dangling,
+        // and writing through it would create its target wherever that is
+        let dangling = resolved.ancestors().any(|ancestor| ancestor.is_symlink());
+        if !allowed || dangling {
+            let action = match access {
+                Access::ReadOnly => "read",
+                Access::ReadWrite => "write",
+            };
+            return Err(NativeError::Error(
+                ErrorKind::Permission,
+                format!("Not allowed to {} '{}'.", action, path),
+            ));
+        }
+        Ok(resolved)
+    }
+
+    /// Whether `path`, already resolved, is one of the roots themselves.
+    pub fn is_root(&self, path: &Path) -> bool {
+        self.roots.iter().any(|(root, _)| root == path)
+    }
+}
+
+pub fn exports() -> Vec<(&'static str, LiteralTypes)> {
+    vec![
+        native("read_text", 1..=1, Access::ReadOnly, |path, _| {
+            let text = fs::read_to_string(path).map_err(|err| io_error("read", path, err))?;
+            Ok(LiteralTypes::String(text))
+        }),
+        native("read_lines", 1..=1, Access::ReadOnly, |path, _| {
+            let text = fs::read_to_string(path).map_err(|err| io_error("read", path, err))?;
+            let lines = text
+                .lines()
+                .map(|line| LiteralTypes::String(line.to_string()))
+                .collect();
+            Ok(LiteralTypes::List(Rc::new(RefCell::new(lines))))
+        }),
+        native("write_text", 2..=2, Access::ReadWrite, |path, args| {
+            fs::write(path, text(&args[1])?).map_err(|err| io_error("write", path, err))?;
+            Ok(LiteralTypes::Nil)
+        }),
+        native("append", 2..=2, Access::ReadWrite, |path, args| {
+            let text = text(&args[1])?;
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(text.as_bytes()))
+                .map_err(|err| io_error("append to", path, err))?;
+            Ok(LiteralTypes::Nil)
+        }),
+        native("exists", 1..=1, Access::ReadOnly, |path, _| {
+            Ok(LiteralTypes::Bool(path.exists()))
+        }),
+        native("list_dir", 1..=1, Access::ReadOnly, |path, _| {
+            let entries = fs::read_dir(path).map_err(|err| io_error("list", path, err))?;
+            let mut names = Vec::new();
+            for entry in entries {
+                let entry = entry.map_err(|err| io_error("list", path, err))?;
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+            names.sort();
+            let names = names.into_iter().map(LiteralTypes::String).collect();
+            Ok(LiteralTypes::List(Rc::new(RefCell::new(names))))
+        }),
+        ("remove", Callable::native("remove", 1..=1, remove)),
+    ]
+}
+
+/// `fs.remove(path)`, which may empty a root but not take the root itself away.
+fn remove(
+    interpreter: &mut Interpreter,
+    args: Vec<LiteralTypes>,
+) -> Result<LiteralTypes, NativeError> {
+    let sandbox = interpreter.sandbox();
+    let path = sandbox.check(text(&args[0])?, Access::ReadWrite)?;
+    if sandbox.is_root(&path) {
+        return Err(NativeError::Error(
+            ErrorKind::Permission,
+            format!("Not allowed to remove the root '{}'.", path.display()),
+        ));
+    }
+    let removed = if path.is_dir() {
+        fs::remove_dir(&path)
+    } else {
+        fs::remove_file(&path)
+    };
+    removed.map_err(|err| io_error("remove", &path, err))?;
+    Ok(LiteralTypes::Nil)
+}
+
+/// A native whose first argument is a path, checked against the interpreter's sandbox
+/// for `access` before `function` sees it.
+fn native(
+    name: &'static str,
+    arity: std::ops::RangeInclusive<usize>,
+    access: Access,
+    function: impl Fn(&Path, &[LiteralTypes]) -> Result<LiteralTypes, NativeError> + 'static,
+) -> (&'static str, LiteralTypes) {
+    let native = Callable::native(name, arity, move |interpreter: &mut Interpreter, args| {
+        let path = interpreter.sandbox().check(text(&args[0])?, access)?;
+        function(&path, &args)
+    });
+    (name, native)
+}
+
+/// The absolute form of `path` with symlinks followed as far as the path exists, and
+/// `.` and `..` applied lexically to the rest.
+fn resolve(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+    let mut resolved = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            component => {
+                resolved.push(component);
+                if let Ok(real) = resolved.canonicalize() {
+                    resolved = real;
+                }
+            }
+        }
+    }
+    resolved
+}
+
+fn text(value: &LiteralTypes) -> Result<&str, NativeError> {
+    match value {
+        LiteralTypes::String(text) => Ok(text),
+        other => Err(NativeError::type_error(format!(
+            "Expected a string, got {}.",
+            other.type_name()
+        ))),
+    }
+}
+
+fn io_error(action: &str, path: &Path, err: std::io::Error) -> NativeError {
+    NativeError::Error(
+        ErrorKind::Io,
+        format!("Can't {} '{}': {}.", action, path.display(), err),
+    )
+}
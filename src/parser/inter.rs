@@ -1,29 +1,247 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::HashMap,
+    fmt, fs,
+    io::{BufRead, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
-use crate::lexer::{Token, TokenType::*};
+use crate::{
+    lexer::{Token, TokenType::*},
+    number::{BigInt, Decimal, RoundingMode},
+};
 
 use super::{
-    ast::Expr,
-    env::Environment,
+    ast::{Expr, MatchArm, Target},
+    callable::{Callable, NativeError},
+    enums::{EnumType, VariantDef},
+    env::{EnvError, Environment},
+    exception::{ErrorKind, ErrorObject},
+    fs::{Access, Sandbox},
+    iter::{Iter, Range},
+    list, map,
+    module::{self, Module},
+    natives,
+    output::{Callback, Output},
+    process::Process,
+    re,
     stmt::{self, Stmt},
-    LiteralTypes, Visitor,
+    string,
+    time::{Clock, SystemClock},
+    Error, LiteralTypes, Parser, Visitor,
 };
 
+/// Largest integer `n` such that every integer in `-n..=n` is exactly representable as `f64`.
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// The most digits `**` may produce, so a typo can't hang the interpreter.
+const MAX_POWER_DIGITS: f64 = 100_000.0;
+
+/// Digits kept after the point when a decimal division doesn't terminate.
+const DECIMAL_DIVISION_SCALE: u32 = 20;
+
 pub struct Interpreter {
+    /// The natives, which enclose the main script and every module.
+    globals: Rc<RefCell<Environment>>,
     environment: Rc<RefCell<Environment>>,
+    /// How decimal division rounds its last kept digit.
+    rounding: RoundingMode,
+    /// The file being run, which relative imports resolve against.
+    script: Option<PathBuf>,
+    /// Directories searched for imports not found next to the importing file.
+    search_path: Vec<PathBuf>,
+    /// Modules of natives, by the name they are imported as.
+    builtins: HashMap<std::string::String, Rc<Module>>,
+    /// Every module loaded so far, by canonical path.
+    modules: HashMap<PathBuf, Rc<Module>>,
+    /// The files currently being imported, outermost first.
+    importing: Vec<PathBuf>,
+    /// Where the `fs` module may read and write.
+    sandbox: Sandbox,
+    /// The arguments, environment and stdin scripts read.
+    process: Process,
+    /// What `clock()` and the `time` module read.
+    clock: Rc<dyn Clock>,
+    /// Where `print` and warnings go.
+    output: Output,
+}
+
+/// How [`Interpreter::bind`] introduces the names of a target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Binding {
+    Assign,
+    Var,
+    Const,
 }
 
 #[derive(Debug, Clone)]
-enum RuntimeError {
-    String(std::string::String),
+pub enum RuntimeError {
+    /// An error on its way out to the nearest `catch`.
+    Error(Rc<ErrorObject>),
+    /// `exit(code)` was called; nothing catches this.
+    Exit(i32),
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeError::Error(error) => write!(f, "line:{} {}", error.line, error.message),
+            RuntimeError::Exit(code) => write!(f, "Exited with code {}", code),
+        }
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
-        Self {
-            environment: Rc::new(RefCell::new(Environment::new())),
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        let mut interpreter = Self {
+            globals: globals.clone(),
+            environment: globals,
+            rounding: RoundingMode::default(),
+            script: None,
+            search_path: Vec::new(),
+            builtins: HashMap::new(),
+            modules: HashMap::new(),
+            importing: Vec::new(),
+            sandbox: Sandbox::default(),
+            process: Process::default(),
+            clock: Rc::new(SystemClock::default()),
+            output: Output::default(),
+        };
+        natives::define_core(&mut interpreter);
+        interpreter
+    }
+
+    /// Make a Rust function callable from scripts as the global `name`.
+    pub fn define_native(
+        &mut self,
+        name: &str,
+        arity: RangeInclusive<usize>,
+        function: impl Fn(&mut Interpreter, Vec<LiteralTypes>) -> Result<LiteralTypes, NativeError>
+            + 'static,
+    ) {
+        let native = Callable::native(name, arity, function);
+        self.globals.borrow_mut().define(name.to_string(), native);
+    }
+
+    /// Make `exports` importable as the module `name`, ahead of any file of that name.
+    pub fn define_module(&mut self, name: &str, exports: Vec<(&str, LiteralTypes)>) {
+        let mut env = Environment::new();
+        for (export, value) in exports {
+            env.define(export.to_string(), value);
         }
+        let module = Module {
+            path: PathBuf::from(name),
+            env: Rc::new(RefCell::new(env)),
+        };
+        self.builtins.insert(name.to_string(), Rc::new(module));
+    }
+
+    /// Run code from the file at `path`, so its imports resolve relative to it.
+    pub fn with_script(mut self, path: &Path) -> Self {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.importing = vec![path.clone()];
+        self.script = Some(path);
+        self
+    }
+
+    pub fn with_search_path(mut self, search_path: Vec<PathBuf>) -> Self {
+        self.search_path = search_path;
+        self
+    }
+
+    /// Let the `fs` module into `root` and everything below it. Without any roots,
+    /// every file access is denied.
+    ///
+    /// ```
+    /// use rlox::{Access, Interpreter};
+    ///
+    /// let root = std::env::temp_dir();
+    /// let mut interpreter = Interpreter::new().with_fs_root(&root, Access::ReadOnly);
+    /// let source = format!("import \"fs\" as fs; exit(fs.exists({:?}) ? 0 : 1);", root);
+    /// assert_eq!(rlox::run_in(&mut interpreter, &source), 0);
+    /// assert_eq!(rlox::run_in(&mut Interpreter::new(), &source), 70);
+    /// ```
+    pub fn with_fs_root(mut self, root: &Path, access: Access) -> Self {
+        self.sandbox.allow(root, access);
+        self
+    }
+
+    pub fn sandbox(&self) -> &Sandbox {
+        &self.sandbox
+    }
+
+    /// What `args()` returns.
+    pub fn with_args(mut self, args: Vec<std::string::String>) -> Self {
+        self.process.args = args;
+        self
     }
+
+    /// Where `env(name)` looks variables up, instead of the process environment.
+    pub fn with_env(mut self, env: impl Fn(&str) -> Option<std::string::String> + 'static) -> Self {
+        self.process.env = Box::new(env);
+        self
+    }
+
+    /// Where `read_line()`, `read_all_stdin()` and `input()` read from, instead of stdin.
+    pub fn with_stdin(mut self, stdin: impl BufRead + 'static) -> Self {
+        self.process.stdin = Box::new(stdin);
+        self
+    }
+
+    pub fn process(&mut self) -> &mut Process {
+        &mut self.process
+    }
+
+    /// Read the time from `clock` instead of the system, e.g. a [`FakeClock`] the
+    /// embedder keeps a handle to and advances.
+    ///
+    /// [`FakeClock`]: super::time::FakeClock
+    pub fn with_clock(mut self, clock: Rc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Where `print` writes, instead of stdout.
+    pub fn with_stdout(mut self, stdout: impl Write + 'static) -> Self {
+        self.output.stdout = Box::new(stdout);
+        self
+    }
+
+    /// Hand everything `print` writes to `callback`, a line at a time with its newline.
+    /// An `input()` prompt, which has none, is handed over when the prompt is shown.
+    pub fn with_stdout_fn(self, callback: impl FnMut(&str) + 'static) -> Self {
+        self.with_stdout(Callback::new(callback))
+    }
+
+    /// Where warnings and uncaught errors go, instead of stderr.
+    pub fn with_stderr(mut self, stderr: impl Write + 'static) -> Self {
+        self.output.stderr = Box::new(stderr);
+        self
+    }
+
+    /// Like [`with_stdout_fn`](Self::with_stdout_fn), for warnings and errors.
+    pub fn with_stderr_fn(self, callback: impl FnMut(&str) + 'static) -> Self {
+        self.with_stderr(Callback::new(callback))
+    }
+
+    pub fn output(&mut self) -> &mut Output {
+        &mut self.output
+    }
+
     pub fn interpret(&mut self, stmt: &[Stmt]) -> Result<(), RuntimeError> {
         for i in stmt {
             self.execute(i)?;
@@ -32,16 +250,106 @@ impl Interpreter {
         Ok(())
     }
     fn execute_block(&mut self, stmt: &[Stmt], env: Environment) -> Result<(), RuntimeError> {
-        let previous = self.environment.clone();
-        self.environment = Rc::new(RefCell::new(env));
-        for s in stmt {
-            self.execute(&s)?;
+        let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(env)));
+        // restore the scope whether the block finished or failed
+        let result = stmt.iter().try_for_each(|s| self.execute(s));
+        self.environment = previous;
+
+        result
+    }
+    /// The module `spec` names, running it first unless it was already imported.
+    fn import(&mut self, keyword: &Token, spec: &str) -> Result<Rc<Module>, RuntimeError> {
+        let import_error =
+            |message: std::string::String| self.typed_error(ErrorKind::Import, keyword, &message);
+        if let Some(module) = self.builtins.get(spec) {
+            return Ok(module.clone());
+        }
+        let path = module::resolve(spec, self.script.as_deref(), &self.search_path)
+            .map_err(import_error)?;
+        if self.importing.contains(&path) {
+            return Err(import_error(module::describe_cycle(&self.importing, &path)));
         }
+        if let Some(module) = self.modules.get(&path) {
+            return Ok(module.clone());
+        }
+
+        let source = fs::read_to_string(&path)
+            .map_err(|err| import_error(format!("Can't read module '{}': {}.", spec, err)))?;
+        let statements = match Parser::new(&source).parse() {
+            Ok(statements) => statements,
+            Err(Error::String(message)) => {
+                return Err(import_error(format!("In module '{}': {}", spec, message)))
+            }
+        };
 
+        let env = Rc::new(RefCell::new(Environment::new_with_enclosing(
+            self.globals.clone(),
+        )));
+        let previous = std::mem::replace(&mut self.environment, env.clone());
+        let importer = self.script.replace(path.clone());
+        self.importing.push(path.clone());
+        let result = statements.iter().try_for_each(|s| self.execute(s));
+        self.importing.pop();
+        self.script = importer;
         self.environment = previous;
+        result?;
+
+        let module = Rc::new(Module {
+            path: path.clone(),
+            env,
+        });
+        self.modules.insert(path, module.clone());
+        Ok(module)
+    }
 
-        Ok(())
+    /// Call `callee` with arguments already evaluated; `paren` is where errors point.
+    fn call(
+        &mut self,
+        paren: &Token,
+        callee: LiteralTypes,
+        arguments: Vec<LiteralTypes>,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        let LiteralTypes::Callable(callable) = callee else {
+            return Err(self.typed_error(
+                ErrorKind::Type,
+                paren,
+                &format!("Can't call a {}.", callee.type_name()),
+            ));
+        };
+        callable
+            .check_arity(arguments.len())
+            .map_err(|message| self.typed_error(ErrorKind::Type, paren, &message))?;
+
+        callable
+            .call(self, arguments)
+            .map_err(|error| self.native_error(paren, error))
+    }
+
+    /// An error from native code, attributed to the call at `paren`.
+    fn native_error(&self, paren: &Token, error: NativeError) -> RuntimeError {
+        match error {
+            NativeError::Exit(code) => RuntimeError::Exit(code),
+            NativeError::Error(kind, message) => self.typed_error(kind, paren, &message),
+        }
+    }
+
+    /// `object.name`
+    fn property(&self, object: &LiteralTypes, name: &Token) -> Result<LiteralTypes, RuntimeError> {
+        let property = match object {
+            LiteralTypes::Enum(ty) => ty.get(&name.lexeme),
+            LiteralTypes::Variant(variant) => variant.get(&name.lexeme),
+            LiteralTypes::Error(error) => error.get(&name.lexeme),
+            LiteralTypes::Module(module) => module.get(name),
+            LiteralTypes::Regex(regex) => re::get(regex, &name.lexeme),
+            _ => Err(format!(
+                "Undefined property '{}' on {}.",
+                name.lexeme,
+                object.type_name()
+            )),
+        };
+        property.map_err(|message| self.typed_error(ErrorKind::Type, name, &message))
     }
+
     fn execute(&mut self, stmt: &Stmt) -> Result<(), RuntimeError> {
         stmt.accept(self)
     }
@@ -49,43 +357,498 @@ impl Interpreter {
     fn evaluate(&mut self, ast: &Expr) -> Result<LiteralTypes, RuntimeError> {
         ast.accept(self)
     }
-    fn error(&self, token: &Token, message: &str) -> RuntimeError {
-        let line = token.line;
-
-        let msg = format!("line:{line} {message}");
 
-        RuntimeError::String(msg)
+    /// Whether `arm` fires for `value`: one of its patterns matches and its guard, if any, holds.
+    fn match_arm<B>(
+        &mut self,
+        arm: &MatchArm<B>,
+        value: &LiteralTypes,
+    ) -> Result<bool, RuntimeError> {
+        if !arm.patterns.iter().any(|p| p.matches(value)) {
+            return Ok(false);
+        }
+        match &arm.guard {
+            Some(guard) => Ok(self.evaluate(guard)?.is_true()),
+            None => Ok(true),
+        }
+    }
+    fn no_arm_matched(&mut self, keyword: &Token, value: &LiteralTypes) {
+        // a warning that can't be written isn't worth failing the script over
+        let _ = writeln!(
+            self.output.stderr,
+            "[Line {}] Warning: No match arm matched {}.",
+            keyword.line,
+            value.stringify()
+        );
     }
-}
 
-impl Visitor<Result<LiteralTypes, RuntimeError>> for Interpreter {
-    fn visit_binary(&mut self, expr: &super::ast::Binary) -> Result<LiteralTypes, RuntimeError> {
-        let left = self.evaluate(&expr.left)?;
+    /// Apply the arithmetic operator `op` to two evaluated operands.
+    ///
+    /// Ints stay ints (except under `/`, which is true division) and overflow into big
+    /// ints. Decimals absorb ints and big ints but refuse floats, and anything else
+    /// numeric mixed with a float becomes a float.
+    fn arithmetic(
+        &self,
+        op: &Token,
+        left: LiteralTypes,
+        right: LiteralTypes,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        match (op.ttype.clone(), left, right) {
+            (Plus, LiteralTypes::String(left), LiteralTypes::String(right)) => {
+                Ok(LiteralTypes::String(format!("{}{}", left, right)))
+            }
+            (Amp | Pipe | Caret | LessLess | GreaterGreater, left, right) => {
+                self.bitwise(op, &left, &right)
+            }
+            (_, LiteralTypes::Int(left), LiteralTypes::Int(right)) => {
+                self.int_arithmetic(op, left, right)
+            }
+            (_, LiteralTypes::Decimal(_), LiteralTypes::Number(_))
+            | (_, LiteralTypes::Number(_), LiteralTypes::Decimal(_)) => Err(self.typed_error(
+                ErrorKind::Type,
+                op,
+                "Cannot mix decimal and float operands; convert one side explicitly",
+            )),
+            (_, left @ LiteralTypes::Decimal(_), right)
+            | (_, left, right @ LiteralTypes::Decimal(_)) => {
+                match (left.to_decimal(), right.to_decimal()) {
+                    (Some(left), Some(right)) => self.decimal_arithmetic(op, &left, &right),
+                    _ => Err(self.typed_error(ErrorKind::Type, op, "Operand must be two number")),
+                }
+            }
+            (ttype, left, right) => match (left.to_bigint(), right.to_bigint()) {
+                (Some(left), Some(right)) => self.big_arithmetic(op, &left, &right),
+                _ => match (left.as_f64(), right.as_f64()) {
+                    (Some(left), Some(right)) => self.float_arithmetic(op, left, right),
+                    _ if ttype == Plus => Err(self.typed_error(
+                        ErrorKind::Type,
+                        op,
+                        "Operand must be two numbers or two strings",
+                    )),
+                    _ => Err(self.typed_error(ErrorKind::Type, op, "Operand must be two number")),
+                },
+            },
+        }
+    }
 
-        let right = self.evaluate(&expr.right)?;
+    fn float_arithmetic(
+        &self,
+        op: &Token,
+        left: f64,
+        right: f64,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        match op.ttype {
+            Plus => Ok(LiteralTypes::Number(left + right)),
+            Minus => Ok(LiteralTypes::Number(left - right)),
+            Star => Ok(LiteralTypes::Number(left * right)),
+            Slash | Percent | TildeSlash if right == 0.0 => {
+                Err(self.typed_error(ErrorKind::Arithmetic, op, "0 cannot be used as a divisor"))
+            }
+            Slash => Ok(LiteralTypes::Number(left / right)),
+            // `%` and `~/` floor, so `a == (a ~/ b) * b + a % b` and the remainder takes
+            // the sign of the divisor: `-7 % 3 == 2`, `-7 ~/ 3 == -3`.
+            Percent => Ok(LiteralTypes::Number(left - right * (left / right).floor())),
+            TildeSlash => Ok(LiteralTypes::Number((left / right).floor())),
+            StarStar => {
+                if left == 0.0 && right < 0.0 {
+                    return Err(self.typed_error(
+                        ErrorKind::Arithmetic,
+                        op,
+                        "0 cannot be raised to a negative power",
+                    ));
+                }
+                if left < 0.0 && right.fract() != 0.0 {
+                    return Err(self.typed_error(
+                        ErrorKind::Arithmetic,
+                        op,
+                        "A negative base requires an integral exponent",
+                    ));
+                }
+                Ok(LiteralTypes::Number(left.powf(right)))
+            }
+            _ => Err(self.typed_error(ErrorKind::Type, op, "Operand must be two number")),
+        }
+    }
 
-        match (expr.op.ttype.clone(), left, right) {
-            (Plus, LiteralTypes::Number(left), LiteralTypes::Number(right)) => {
-                Ok(LiteralTypes::Number(left + right))
+    /// Int arithmetic is checked: a result outside `i64` is redone with big ints rather
+    /// than wrapping.
+    fn int_arithmetic(
+        &self,
+        op: &Token,
+        left: i64,
+        right: i64,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        let value = match op.ttype {
+            Plus => left.checked_add(right),
+            Minus => left.checked_sub(right),
+            Star => left.checked_mul(right),
+            Slash => return self.float_arithmetic(op, left as f64, right as f64),
+            Percent | TildeSlash if right == 0 => {
+                return Err(self.typed_error(
+                    ErrorKind::Arithmetic,
+                    op,
+                    "0 cannot be used as a divisor",
+                ))
             }
-            (Plus, LiteralTypes::String(left), LiteralTypes::String(right)) => {
-                Ok(LiteralTypes::String(format!("{}{}", left, right)))
+            Percent => {
+                let rem = left.wrapping_rem(right);
+                Some(if rem != 0 && (rem < 0) != (right < 0) {
+                    rem + right
+                } else {
+                    rem
+                })
             }
-            (Plus, _, _) => Err(self.error(&expr.op, "Operand must be two numbers or two strings")),
-            (Minus, LiteralTypes::Number(left), LiteralTypes::Number(right)) => {
-                Ok(LiteralTypes::Number(left - right))
+            TildeSlash => left.checked_div(right).map(|quot| {
+                if left % right != 0 && (left < 0) != (right < 0) {
+                    quot - 1
+                } else {
+                    quot
+                }
+            }),
+            StarStar if right < 0 => return self.float_arithmetic(op, left as f64, right as f64),
+            StarStar => u32::try_from(right)
+                .ok()
+                .and_then(|exp| left.checked_pow(exp)),
+            _ => return Err(self.typed_error(ErrorKind::Type, op, "Operand must be two number")),
+        };
+
+        match value {
+            Some(value) => Ok(LiteralTypes::Int(value)),
+            None => self.big_arithmetic(op, &BigInt::from(left), &BigInt::from(right)),
+        }
+    }
+
+    fn big_arithmetic(
+        &self,
+        op: &Token,
+        left: &BigInt,
+        right: &BigInt,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        let value = match op.ttype {
+            Plus => left + right,
+            Minus => left - right,
+            Star => left * right,
+            Slash => return self.float_arithmetic(op, left.to_f64(), right.to_f64()),
+            Percent | TildeSlash if right.is_zero() => {
+                return Err(self.typed_error(
+                    ErrorKind::Arithmetic,
+                    op,
+                    "0 cannot be used as a divisor",
+                ))
+            }
+            Percent => left.div_mod_floor(right).unwrap_or_default().1,
+            TildeSlash => left.div_mod_floor(right).unwrap_or_default().0,
+            StarStar if right.is_negative() => {
+                return self.float_arithmetic(op, left.to_f64(), right.to_f64())
             }
-            (Star, LiteralTypes::Number(left), LiteralTypes::Number(right)) => {
-                Ok(LiteralTypes::Number(left * right))
+            StarStar => match right.to_i64().and_then(|exp| u32::try_from(exp).ok()) {
+                Some(exp) if magnitude(left) * exp as f64 <= MAX_POWER_DIGITS => left.pow(exp),
+                _ => {
+                    return Err(self.typed_error(
+                        ErrorKind::Arithmetic,
+                        op,
+                        "Exponent is too large",
+                    ))
+                }
+            },
+            _ => return Err(self.typed_error(ErrorKind::Type, op, "Operand must be two number")),
+        };
+
+        Ok(LiteralTypes::integer(value))
+    }
+
+    fn decimal_arithmetic(
+        &self,
+        op: &Token,
+        left: &Decimal,
+        right: &Decimal,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        let divisor_error =
+            || self.typed_error(ErrorKind::Arithmetic, op, "0 cannot be used as a divisor");
+        let value = match op.ttype {
+            Plus => left.add(right),
+            Minus => left.sub(right),
+            Star => left.mul(right),
+            Slash => left
+                .div(right, DECIMAL_DIVISION_SCALE, self.rounding)
+                .ok_or_else(divisor_error)?,
+            TildeSlash => Decimal::from(left.div_floor(right).ok_or_else(divisor_error)?),
+            Percent => {
+                let quot = Decimal::from(left.div_floor(right).ok_or_else(divisor_error)?);
+                left.sub(&right.mul(&quot))
             }
-            (Slash, LiteralTypes::Number(left), LiteralTypes::Number(right)) => {
-                if right == 0.0 {
-                    return Err(self.error(&expr.op, "0 cannot be used as a divisor"));
+            StarStar => {
+                let exp = right
+                    .to_integer()
+                    .and_then(|exp| exp.to_i64())
+                    .and_then(|exp| i32::try_from(exp).ok())
+                    .ok_or_else(|| {
+                        self.typed_error(
+                            ErrorKind::Arithmetic,
+                            op,
+                            "Decimal exponent must be a small integer",
+                        )
+                    })?;
+                // every multiplication adds the scale again, so that grows too
+                let digits = left.to_f64().abs().log10().max(0.0) + left.scale() as f64;
+                if digits * exp.unsigned_abs() as f64 > MAX_POWER_DIGITS {
+                    return Err(self.typed_error(
+                        ErrorKind::Arithmetic,
+                        op,
+                        "Exponent is too large",
+                    ));
                 }
-                Ok(LiteralTypes::Number(left / right))
+                let power = left.pow(exp.unsigned_abs());
+                if exp >= 0 {
+                    power
+                } else {
+                    Decimal::from(BigInt::from(1))
+                        .div(&power, DECIMAL_DIVISION_SCALE, self.rounding)
+                        .ok_or_else(|| {
+                            self.typed_error(
+                                ErrorKind::Arithmetic,
+                                op,
+                                "0 cannot be raised to a negative power",
+                            )
+                        })?
+                }
+            }
+            _ => return Err(self.typed_error(ErrorKind::Type, op, "Operand must be two number")),
+        };
+
+        Ok(LiteralTypes::Decimal(value))
+    }
+
+    /// Bitwise operators work on ints, and on floats that are whole and within
+    /// `±(2^53 - 1)`. The result is always an int.
+    fn bitwise(
+        &self,
+        op: &Token,
+        left: &LiteralTypes,
+        right: &LiteralTypes,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        let left = self.integral(op, left)?;
+        let right = self.integral(op, right)?;
+
+        let value = match op.ttype {
+            Amp => Some(left & right),
+            Pipe => Some(left | right),
+            Caret => Some(left ^ right),
+            LessLess | GreaterGreater if !(0..64).contains(&right) => {
+                return Err(self.typed_error(
+                    ErrorKind::Arithmetic,
+                    op,
+                    "Shift amount must be between 0 and 63",
+                ));
             }
-            _ => Err(self.error(&expr.op, "Operand must be two number")),
+            LessLess => i64::try_from((left as i128) << right).ok(),
+            GreaterGreater => Some(left >> right),
+            _ => return Err(self.error(op, "Unknown bitwise operator")),
+        };
+
+        value.map(LiteralTypes::Int).ok_or_else(|| {
+            self.typed_error(
+                ErrorKind::Arithmetic,
+                op,
+                &format!("Integer overflow in '{}'", op.lexeme),
+            )
+        })
+    }
+    fn integral(&self, op: &Token, value: &LiteralTypes) -> Result<i64, RuntimeError> {
+        match value {
+            LiteralTypes::Int(num) => Ok(*num),
+            LiteralTypes::Number(num)
+                if num.fract() == 0.0 && num.abs() <= MAX_SAFE_INTEGER as f64 =>
+            {
+                Ok(*num as i64)
+            }
+            LiteralTypes::Number(num) => Err(self.typed_error(
+                ErrorKind::Arithmetic,
+                op,
+                &format!(
+                    "Operand of '{}' must be an integer within the safe range, got {}",
+                    op.lexeme, num
+                ),
+            )),
+            LiteralTypes::BigInt(num) => Err(self.typed_error(
+                ErrorKind::Arithmetic,
+                op,
+                &format!(
+                    "Operand of '{}' must be an integer within 64 bits, got {}",
+                    op.lexeme, num
+                ),
+            )),
+            _ => Err(self.typed_error(
+                ErrorKind::Type,
+                op,
+                &format!("Operand of '{}' must be a number", op.lexeme),
+            )),
+        }
+    }
+
+    fn index_get(
+        &self,
+        bracket: &Token,
+        object: &LiteralTypes,
+        index: &LiteralTypes,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        match object {
+            LiteralTypes::List(list) => {
+                let list = list.borrow();
+                let at = list::position(index, list.len(), "list")
+                    .map_err(|message| self.typed_error(ErrorKind::Index, bracket, &message))?;
+                Ok(list[at].clone())
+            }
+            LiteralTypes::Tuple(items) => {
+                let at = list::position(index, items.len(), "tuple")
+                    .map_err(|message| self.typed_error(ErrorKind::Index, bracket, &message))?;
+                Ok(items[at].clone())
+            }
+            LiteralTypes::String(text) => string::char_at(text, index)
+                .map_err(|message| self.typed_error(ErrorKind::Index, bracket, &message)),
+            LiteralTypes::Map(map) => match map.borrow().get(index) {
+                Ok(Some(value)) => Ok(value),
+                Ok(None) => Err(self.typed_error(
+                    ErrorKind::Key,
+                    bracket,
+                    &format!("Undefined key {}.", index.stringify()),
+                )),
+                Err(message) => Err(self.typed_error(ErrorKind::Type, bracket, &message)),
+            },
+            _ => Err(self.typed_error(
+                ErrorKind::Type,
+                bracket,
+                &format!("Can't index a {}.", object.type_name()),
+            )),
+        }
+    }
+    fn index_set(
+        &self,
+        bracket: &Token,
+        object: &LiteralTypes,
+        index: LiteralTypes,
+        value: LiteralTypes,
+    ) -> Result<(), RuntimeError> {
+        match object {
+            LiteralTypes::List(list) => {
+                let mut list = list.borrow_mut();
+                let at = list::position(&index, list.len(), "list")
+                    .map_err(|message| self.typed_error(ErrorKind::Index, bracket, &message))?;
+                list[at] = value;
+                Ok(())
+            }
+            LiteralTypes::Map(map) => map
+                .borrow_mut()
+                .insert(index, value)
+                .map_err(|message| self.typed_error(ErrorKind::Type, bracket, &message)),
+            _ => Err(self.typed_error(
+                ErrorKind::Type,
+                bracket,
+                &format!("Can't assign to an index of a {}.", object.type_name()),
+            )),
+        }
+    }
+
+    /// Bind `value` to `target`, either declaring new variables or assigning to
+    /// existing ones.
+    fn bind(
+        &mut self,
+        target: &Target,
+        value: LiteralTypes,
+        binding: Binding,
+    ) -> Result<(), RuntimeError> {
+        let (open, items, rest) = match target {
+            Target::Name(name) if binding == Binding::Assign => {
+                return self.assign_variable(name, value)
+            }
+            Target::Name(name) => {
+                let constant = binding == Binding::Const;
+                return self
+                    .environment
+                    .borrow_mut()
+                    .declare(name.lexeme.clone(), value, constant)
+                    .map_err(|_| {
+                        let message = format!("Can't redeclare constant '{}'.", name.lexeme);
+                        self.typed_error(ErrorKind::Name, name, &message)
+                    });
+            }
+            Target::Sequence { open, items, rest } => (open, items, rest),
+        };
+
+        let elements = match &value {
+            LiteralTypes::Tuple(elements) => elements.to_vec(),
+            LiteralTypes::List(list) => list.borrow().clone(),
+            _ => {
+                return Err(self.typed_error(
+                    ErrorKind::Type,
+                    open,
+                    &format!("Can't destructure a {}.", value.type_name()),
+                ))
+            }
+        };
+        let arity_ok = match rest {
+            Some(_) => elements.len() >= items.len(),
+            None => elements.len() == items.len(),
+        };
+        if !arity_ok {
+            let at_least = if rest.is_some() { "at least " } else { "" };
+            return Err(self.typed_error(
+                ErrorKind::Type,
+                open,
+                &format!(
+                    "Expected {}{} values to unpack but got {}.",
+                    at_least,
+                    items.len(),
+                    elements.len()
+                ),
+            ));
         }
+
+        let mut elements = elements.into_iter();
+        for (item, element) in items.iter().zip(elements.by_ref()) {
+            self.bind(item, element, binding)?;
+        }
+        if let Some(rest) = rest {
+            let remaining: Vec<LiteralTypes> = elements.collect();
+            let remaining = match value {
+                LiteralTypes::Tuple(_) => LiteralTypes::Tuple(remaining.into()),
+                _ => LiteralTypes::List(Rc::new(RefCell::new(remaining))),
+            };
+            self.bind(&Target::Name(rest.clone()), remaining, binding)?;
+        }
+
+        Ok(())
+    }
+
+    fn assign_variable(&self, name: &Token, value: LiteralTypes) -> Result<(), RuntimeError> {
+        let message = match self.environment.borrow_mut().assign(name, value) {
+            Ok(()) => return Ok(()),
+            Err(EnvError::Undefined) => format!("Undefined variable '{}'.", name.lexeme),
+            Err(EnvError::Constant) => format!("Can't assign to constant '{}'.", name.lexeme),
+        };
+        Err(self.typed_error(ErrorKind::Name, name, &message))
+    }
+
+    fn error(&self, token: &Token, message: &str) -> RuntimeError {
+        self.typed_error(ErrorKind::Runtime, token, message)
+    }
+    fn typed_error(&self, kind: ErrorKind, token: &Token, message: &str) -> RuntimeError {
+        RuntimeError::Error(Rc::new(ErrorObject {
+            kind,
+            message: message.to_string(),
+            line: token.line,
+            value: LiteralTypes::Nil,
+        }))
+    }
+}
+
+impl Visitor<Result<LiteralTypes, RuntimeError>> for Interpreter {
+    fn visit_binary(&mut self, expr: &super::ast::Binary) -> Result<LiteralTypes, RuntimeError> {
+        let left = self.evaluate(&expr.left)?;
+
+        let right = self.evaluate(&expr.right)?;
+
+        self.arithmetic(&expr.op, left, right)
     }
 
     fn visit_grouping(
@@ -99,11 +862,16 @@ impl Visitor<Result<LiteralTypes, RuntimeError>> for Interpreter {
         let value = self.evaluate(&expr.right)?;
 
         if let Minus = expr.op.ttype {
-            if let LiteralTypes::Number(f) = value {
-                return Ok(LiteralTypes::Number(-f));
-            } else {
-                return Err(self.error(&expr.op, "Minus must be a number"));
-            }
+            return match value {
+                LiteralTypes::Number(f) => Ok(LiteralTypes::Number(-f)),
+                LiteralTypes::Int(i) => Ok(LiteralTypes::integer(-&BigInt::from(i))),
+                LiteralTypes::BigInt(i) => Ok(LiteralTypes::integer(-&i)),
+                LiteralTypes::Decimal(d) => Ok(LiteralTypes::Decimal(d.neg())),
+                _ => Err(self.typed_error(ErrorKind::Type, &expr.op, "Minus must be a number")),
+            };
+        }
+        if let Tilde = expr.op.ttype {
+            return Ok(LiteralTypes::Int(!self.integral(&expr.op, &value)?));
         }
         if let Bang = expr.op.ttype {
             return Ok(match value {
@@ -129,12 +897,10 @@ impl Visitor<Result<LiteralTypes, RuntimeError>> for Interpreter {
         let right = self.evaluate(&expr.right)?;
 
         let bool = match (expr.op.ttype.clone(), left, right) {
-            (Greater, LiteralTypes::Number(left), LiteralTypes::Number(right)) => left > right,
-            (GreaterEqual, LiteralTypes::Number(left), LiteralTypes::Number(right)) => {
-                left >= right
-            }
-            (Less, LiteralTypes::Number(left), LiteralTypes::Number(right)) => left < right,
-            (LessEqual, LiteralTypes::Number(left), LiteralTypes::Number(right)) => left <= right,
+            (Greater, left, right) => left.compare(&right).is_some_and(Ordering::is_gt),
+            (GreaterEqual, left, right) => left.compare(&right).is_some_and(Ordering::is_ge),
+            (Less, left, right) => left.compare(&right).is_some_and(Ordering::is_lt),
+            (LessEqual, left, right) => left.compare(&right).is_some_and(Ordering::is_le),
             (BangEqual, left, right) => !left.equal(&right),
             (EqualEqual, left, right) => left.equal(&right),
             _ => false,
@@ -150,7 +916,8 @@ impl Visitor<Result<LiteralTypes, RuntimeError>> for Interpreter {
         self.environment
             .borrow()
             .get(&expr.identifier)
-            .ok_or(self.error(
+            .ok_or(self.typed_error(
+                ErrorKind::Name,
                 &expr.identifier,
                 &format!("Undefined variable '{}'.", &expr.identifier.lexeme),
             ))
@@ -161,15 +928,7 @@ impl Visitor<Result<LiteralTypes, RuntimeError>> for Interpreter {
         expr: &super::ast::Assign,
     ) -> Result<LiteralTypes, RuntimeError> {
         let value = self.evaluate(&expr.value)?;
-        self.environment
-            .borrow_mut()
-            .assign(&expr.name, value.clone())
-            .map_err(|_| {
-                self.error(
-                    &expr.name,
-                    &format!("Undefined variable '{}'.", expr.name.lexeme),
-                )
-            })?;
+        self.assign_variable(&expr.name, value.clone())?;
 
         Ok(value)
     }
@@ -192,6 +951,231 @@ impl Visitor<Result<LiteralTypes, RuntimeError>> for Interpreter {
 
         self.evaluate(&expr.right)
     }
+
+    fn visit_match_expr(&mut self, expr: &super::ast::Match) -> Result<LiteralTypes, RuntimeError> {
+        let value = self.evaluate(&expr.subject)?;
+
+        for arm in expr.arms.iter() {
+            if self.match_arm(arm, &value)? {
+                return self.evaluate(&arm.body);
+            }
+        }
+
+        self.no_arm_matched(&expr.keyword, &value);
+        Ok(LiteralTypes::Nil)
+    }
+
+    fn visit_compound_expr(
+        &mut self,
+        expr: &super::ast::Compound,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        match expr.target.as_ref() {
+            Expr::Variable(variable) => {
+                let old = self.visit_variable(variable)?;
+                let value = self.evaluate(&expr.value)?;
+                let new = self.arithmetic(&expr.op, old.clone(), value)?;
+                self.assign_variable(&variable.identifier, new.clone())?;
+
+                Ok(if expr.postfix { old } else { new })
+            }
+            Expr::Index(target) => {
+                // the container and the index are evaluated once for both the read and the write
+                let object = self.evaluate(&target.object)?;
+                let index = self.evaluate(&target.index)?;
+                let old = self.index_get(&target.bracket, &object, &index)?;
+                let value = self.evaluate(&expr.value)?;
+                let new = self.arithmetic(&expr.op, old.clone(), value)?;
+                self.index_set(&target.bracket, &object, index, new.clone())?;
+
+                Ok(if expr.postfix { old } else { new })
+            }
+            _ => Err(self.error(&expr.op, "Invalid assignment target.")),
+        }
+    }
+
+    fn visit_list_expr(&mut self, expr: &super::ast::List) -> Result<LiteralTypes, RuntimeError> {
+        let mut elements = Vec::with_capacity(expr.elements.len());
+        for element in expr.elements.iter() {
+            elements.push(self.evaluate(element)?);
+        }
+
+        Ok(LiteralTypes::List(Rc::new(RefCell::new(elements))))
+    }
+
+    fn visit_tuple_expr(&mut self, expr: &super::ast::Tuple) -> Result<LiteralTypes, RuntimeError> {
+        let mut elements = Vec::with_capacity(expr.elements.len());
+        for element in expr.elements.iter() {
+            elements.push(self.evaluate(element)?);
+        }
+
+        Ok(LiteralTypes::Tuple(elements.into()))
+    }
+
+    fn visit_map_expr(&mut self, expr: &super::ast::Map) -> Result<LiteralTypes, RuntimeError> {
+        let mut map = map::Map::default();
+        for (key, value) in expr.entries.iter() {
+            let key = self.evaluate(key)?;
+            let value = self.evaluate(value)?;
+            map.insert(key, value)
+                .map_err(|message| self.typed_error(ErrorKind::Type, &expr.brace, &message))?;
+        }
+
+        Ok(LiteralTypes::Map(Rc::new(RefCell::new(map))))
+    }
+
+    fn visit_range_expr(&mut self, expr: &super::ast::Range) -> Result<LiteralTypes, RuntimeError> {
+        let mut bounds = Vec::with_capacity(3);
+        for bound in [Some(&expr.start), Some(&expr.end), expr.step.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            match self.evaluate(bound)? {
+                LiteralTypes::Int(num) => bounds.push(num),
+                other => {
+                    return Err(self.typed_error(
+                        ErrorKind::Type,
+                        &expr.op,
+                        &format!("Range bounds must be integers, got {}.", other.type_name()),
+                    ))
+                }
+            }
+        }
+        let step = bounds.get(2).copied().unwrap_or(1);
+        let inclusive = expr.op.ttype == DotDotEqual;
+
+        Range::new(bounds[0], bounds[1], step, inclusive)
+            .map(LiteralTypes::Range)
+            .map_err(|message| self.error(&expr.op, &message))
+    }
+
+    fn visit_index_expr(&mut self, expr: &super::ast::Index) -> Result<LiteralTypes, RuntimeError> {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+
+        self.index_get(&expr.bracket, &object, &index)
+    }
+
+    fn visit_slice_expr(&mut self, expr: &super::ast::Slice) -> Result<LiteralTypes, RuntimeError> {
+        let object = self.evaluate(&expr.object)?;
+        let start = match &expr.start {
+            Some(start) => Some(self.evaluate(start)?),
+            None => None,
+        };
+        let end = match &expr.end {
+            Some(end) => Some(self.evaluate(end)?),
+            None => None,
+        };
+
+        if let LiteralTypes::String(text) = &object {
+            let mut args = vec![start.unwrap_or(LiteralTypes::Nil)];
+            args.extend(end);
+            return string::call_method(text, "substr", args).map_err(|error| match error {
+                NativeError::Error(_, message) => {
+                    self.typed_error(ErrorKind::Type, &expr.bracket, &message)
+                }
+                error => self.native_error(&expr.bracket, error),
+            });
+        }
+        let LiteralTypes::List(list) = object else {
+            return Err(self.typed_error(
+                ErrorKind::Type,
+                &expr.bracket,
+                &format!("Can't slice a {}.", object.type_name()),
+            ));
+        };
+        let list = list.borrow();
+        let (start, end) = list::slice_bounds(start.as_ref(), end.as_ref(), list.len())
+            .map_err(|message| self.typed_error(ErrorKind::Type, &expr.bracket, &message))?;
+
+        Ok(LiteralTypes::List(Rc::new(RefCell::new(
+            list[start..end].to_vec(),
+        ))))
+    }
+
+    fn visit_index_set_expr(
+        &mut self,
+        expr: &super::ast::IndexSet,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+        let value = self.evaluate(&expr.value)?;
+
+        self.index_set(&expr.bracket, &object, index, value.clone())?;
+        Ok(value)
+    }
+
+    fn visit_call_expr(&mut self, expr: &super::ast::Call) -> Result<LiteralTypes, RuntimeError> {
+        // `object.name(...)` is a method call on strings, lists, maps, enums and regexes, and
+        // otherwise a call of whatever the property holds
+        let mut method = None;
+        let callee = match expr.callee.as_ref() {
+            Expr::Get(get) => {
+                let object = self.evaluate(&get.object)?;
+                match object {
+                    LiteralTypes::String(_)
+                    | LiteralTypes::List(_)
+                    | LiteralTypes::Map(_)
+                    | LiteralTypes::Enum(_)
+                    | LiteralTypes::Regex(_) => {
+                        method = Some(&get.name);
+                        object
+                    }
+                    _ => self.property(&object, &get.name)?,
+                }
+            }
+            callee => self.evaluate(callee)?,
+        };
+
+        let mut arguments = Vec::with_capacity(expr.arguments.len());
+        for argument in expr.arguments.iter() {
+            arguments.push(self.evaluate(argument)?);
+        }
+
+        let Some(name) = method else {
+            return self.call(&expr.paren, callee, arguments);
+        };
+        let result = match &callee {
+            LiteralTypes::String(text) => {
+                return string::call_method(text, &name.lexeme, arguments)
+                    .map_err(|error| self.native_error(&expr.paren, error))
+            }
+            LiteralTypes::List(list) => {
+                return list::call_method(list, &name.lexeme, arguments)
+                    .map_err(|error| self.native_error(&expr.paren, error))
+            }
+            LiteralTypes::Map(map) => map::call_method(map, &name.lexeme, arguments),
+            LiteralTypes::Enum(ty) => ty.call(&name.lexeme, arguments),
+            LiteralTypes::Regex(regex) => re::call_method(regex, &name.lexeme, arguments),
+            _ => unreachable!("only strings, lists, maps, enums and regexes have methods"),
+        };
+        result.map_err(|message| self.error(&expr.paren, &message))
+    }
+
+    fn visit_destructure_expr(
+        &mut self,
+        expr: &super::ast::Destructure,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        let value = self.evaluate(&expr.value)?;
+        self.bind(&expr.target, value.clone(), Binding::Assign)?;
+
+        Ok(value)
+    }
+
+    fn visit_get_expr(&mut self, expr: &super::ast::Get) -> Result<LiteralTypes, RuntimeError> {
+        let object = self.evaluate(&expr.object)?;
+        self.property(&object, &expr.name)
+    }
+
+    fn visit_conditional_expr(
+        &mut self,
+        expr: &super::ast::Conditional,
+    ) -> Result<LiteralTypes, RuntimeError> {
+        if self.evaluate(&expr.condition)?.is_true() {
+            self.evaluate(&expr.then_branch)
+        } else {
+            self.evaluate(&expr.else_branch)
+        }
+    }
 }
 
 impl stmt::Visitor<Result<(), RuntimeError>> for Interpreter {
@@ -202,10 +1186,11 @@ impl stmt::Visitor<Result<(), RuntimeError>> for Interpreter {
     }
 
     fn visit_print_stmt(&mut self, expr: &stmt::Print) -> Result<(), RuntimeError> {
-        let res = self.evaluate(&expr.expression).unwrap();
-        println!("{}", res.stringify());
-
-        Ok(())
+        let res = self.evaluate(&expr.expression)?;
+        writeln!(self.output.stdout, "{}", res.stringify()).map_err(|err| {
+            let message = format!("Can't write output: {}.", err);
+            self.typed_error(ErrorKind::Io, &expr.keyword, &message)
+        })
     }
 
     fn visit_var_stmt(&mut self, stmt: &stmt::Var) -> Result<(), RuntimeError> {
@@ -214,11 +1199,12 @@ impl stmt::Visitor<Result<(), RuntimeError>> for Interpreter {
             value = self.evaluate(&stmt.initializer)?;
         }
 
-        self.environment
-            .borrow_mut()
-            .define(stmt.name.lexeme.clone(), value);
-
-        Ok(())
+        let binding = if stmt.constant {
+            Binding::Const
+        } else {
+            Binding::Var
+        };
+        self.bind(&stmt.target, value, binding)
     }
 
     fn visit_block_stmt(&mut self, block: &stmt::Block) -> Result<(), RuntimeError> {
@@ -234,7 +1220,7 @@ impl stmt::Visitor<Result<(), RuntimeError>> for Interpreter {
         if self.evaluate(&stmt.condition)?.is_true() {
             self.execute(&stmt.then_branch)?;
         } else if let Some(else_branch) = &stmt.else_branch {
-            self.execute(&else_branch)?;
+            self.execute(else_branch)?;
         }
 
         Ok(())
@@ -243,7 +1229,7 @@ impl stmt::Visitor<Result<(), RuntimeError>> for Interpreter {
     fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Result<(), RuntimeError> {
         loop {
             let cond = self.evaluate(&stmt.condition)?;
-            if cond.is_true() == false {
+            if !cond.is_true() {
                 break;
             }
             self.execute(&stmt.body)?;
@@ -251,10 +1237,145 @@ impl stmt::Visitor<Result<(), RuntimeError>> for Interpreter {
 
         Ok(())
     }
+
+    fn visit_for_in_stmt(&mut self, stmt: &stmt::ForIn) -> Result<(), RuntimeError> {
+        let iterable = self.evaluate(&stmt.iterable)?;
+        let mut iter = Iter::new(&iterable)
+            .map_err(|message| self.typed_error(ErrorKind::Type, &stmt.keyword, &message))?;
+        let keyed = matches!(iterable, LiteralTypes::Map(_));
+
+        while let Some((key, value)) = iter.next() {
+            // a fresh scope per iteration, so every pass gets its own binding
+            let mut env = Environment::new_with_enclosing(self.environment.clone());
+            match stmt.names.as_slice() {
+                [name] if keyed => env.define(name.lexeme.clone(), key),
+                [name] => env.define(name.lexeme.clone(), value),
+                [key_name, value_name] => {
+                    env.define(key_name.lexeme.clone(), key);
+                    env.define(value_name.lexeme.clone(), value);
+                }
+                _ => unreachable!(),
+            }
+            self.execute_block(std::slice::from_ref(stmt.body.as_ref()), env)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_enum_stmt(&mut self, stmt: &stmt::Enum) -> Result<(), RuntimeError> {
+        let variants = stmt
+            .variants
+            .iter()
+            .map(|(name, fields)| VariantDef {
+                name: name.lexeme.clone(),
+                fields: fields.iter().map(|field| field.lexeme.clone()).collect(),
+            })
+            .collect();
+        let ty = EnumType {
+            name: stmt.name.lexeme.clone(),
+            variants,
+        };
+
+        self.environment
+            .borrow_mut()
+            .declare(
+                stmt.name.lexeme.clone(),
+                LiteralTypes::Enum(Rc::new(ty)),
+                true,
+            )
+            .map_err(|_| {
+                let message = format!("Can't redeclare constant '{}'.", stmt.name.lexeme);
+                self.typed_error(ErrorKind::Name, &stmt.name, &message)
+            })
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) -> Result<(), RuntimeError> {
+        let error = match self.evaluate(&stmt.value)? {
+            // rethrowing a caught error keeps where it came from
+            LiteralTypes::Error(error) => error,
+            value => Rc::new(ErrorObject {
+                kind: ErrorKind::Error,
+                message: value.to_text(),
+                line: stmt.keyword.line,
+                value,
+            }),
+        };
+
+        Err(RuntimeError::Error(error))
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) -> Result<(), RuntimeError> {
+        let enclosing = self.environment.clone();
+        let scope = || Environment::new_with_enclosing(enclosing.clone());
+        let mut result = self.execute_block(&stmt.body, scope());
+
+        if let (Err(RuntimeError::Error(error)), Some((name, body))) = (&result, &stmt.catch) {
+            let mut env = scope();
+            if let Some(name) = name {
+                env.define(name.lexeme.clone(), LiteralTypes::Error(error.clone()));
+            }
+            result = self.execute_block(body, env);
+        }
+
+        if let Some(finally) = &stmt.finally {
+            // an error in `finally` replaces whatever was in flight
+            self.execute_block(finally, scope())?;
+        }
+
+        result
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) -> Result<(), RuntimeError> {
+        let spec = stmt.path.literal.to_text();
+        let module = self.import(&stmt.keyword, &spec)?;
+
+        let bindings = match &stmt.names {
+            stmt::ImportNames::Module(name) => vec![(name, LiteralTypes::Module(module))],
+            stmt::ImportNames::Exports(names) => {
+                let mut bindings = Vec::with_capacity(names.len());
+                for name in names {
+                    let value = module
+                        .get(name)
+                        .map_err(|message| self.typed_error(ErrorKind::Import, name, &message))?;
+                    bindings.push((name, value));
+                }
+                bindings
+            }
+        };
+        for (name, value) in bindings {
+            self.bind(&Target::Name(name.clone()), value, Binding::Var)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_match_stmt(&mut self, stmt: &stmt::Match) -> Result<(), RuntimeError> {
+        let value = self.evaluate(&stmt.subject)?;
+
+        for arm in stmt.arms.iter() {
+            if self.match_arm(arm, &value)? {
+                return self.execute(&arm.body);
+            }
+        }
+
+        self.no_arm_matched(&stmt.keyword, &value);
+        Ok(())
+    }
+}
+
+/// About how many decimal digits `value` has; at least 0, even for 0 itself.
+fn magnitude(value: &BigInt) -> f64 {
+    let float = value.to_f64().abs();
+    if float.is_finite() {
+        float.log10().max(0.0)
+    } else {
+        value.abs().to_string().len() as f64
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::parser::time::FakeClock;
     use crate::parser::Parser;
 
     use super::*;
@@ -262,19 +1383,163 @@ mod tests {
     fn number(val: f64) -> LiteralTypes {
         LiteralTypes::Number(val)
     }
+    fn int(val: i64) -> LiteralTypes {
+        LiteralTypes::Int(val)
+    }
     fn get_value(source: &str) -> LiteralTypes {
-        let ast = Parser::new(source).equality().unwrap();
+        let ast = Parser::new(source).expression().unwrap();
 
         let mut inter = Interpreter::new();
         inter.evaluate(&ast).unwrap()
     }
 
+    /// What running `code` prints.
+    fn printed(code: &str) -> std::string::String {
+        let stmt = Parser::new(code).parse().unwrap();
+        let out = Rc::new(RefCell::new(std::string::String::new()));
+        let sink = out.clone();
+
+        let mut inter =
+            Interpreter::new().with_stdout_fn(move |text| sink.borrow_mut().push_str(text));
+        inter.interpret(&stmt).unwrap();
+        let text = out.borrow().clone();
+        text
+    }
+
+    fn get_var(code: &str, name: &str) -> LiteralTypes {
+        let stmt = Parser::new(code).parse().unwrap();
+
+        let mut inter = Interpreter::new();
+        inter.interpret(&stmt).unwrap();
+        var(&inter, name)
+    }
+
+    /// The value of the global `name` in `inter`.
+    fn var(inter: &Interpreter, name: &str) -> LiteralTypes {
+        let token = Token::new(Identifier, name.to_string(), LiteralTypes::Nil, 1);
+        let value = inter.environment.borrow().get(&token).unwrap();
+        value
+    }
+
     #[test]
     fn noraml() {
-        assert_eq!(get_value("-1"), number(-1.0));
-        assert_eq!(get_value("1+ (-2)"), number(-1.0));
+        assert_eq!(get_value("-1"), int(-1));
+        assert_eq!(get_value("1+ (-2)"), int(-1));
+
+        assert_eq!(get_value("1 + 2 * (3+4) - 5"), int(10));
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(get_value("7 % 3"), int(1));
+        assert_eq!(get_value("-7 % 3"), int(2));
+        assert_eq!(get_value("7 % -3"), int(-2));
+        assert_eq!(get_value("7 ~/ 2"), int(3));
+        assert_eq!(get_value("-7 ~/ 2"), int(-4));
+        assert_eq!(get_value("2 ** 3 ** 2"), int(512));
+        assert_eq!(get_value("-2 ** 2"), int(-4));
+        assert_eq!(get_value("2 ** -1"), number(0.5));
+        assert_eq!(get_value("2 * 3 ** 2 % 5"), int(3));
+
+        let ast = Parser::new("1 % 0").expression().unwrap();
+        assert!(Interpreter::new().evaluate(&ast).is_err());
+        let ast = Parser::new("0 ** -1").expression().unwrap();
+        assert!(Interpreter::new().evaluate(&ast).is_err());
+    }
 
-        assert_eq!(get_value("1 + 2 * (3+4) - 5"), number(10.0));
+    #[test]
+    fn int_and_float() {
+        assert_eq!(get_value("7 / 2"), number(3.5));
+        assert_eq!(get_value("1 + 2.5"), number(3.5));
+        assert_eq!(get_value("2 ** 62 + (2 ** 62 - 1)"), int(i64::MAX));
+        assert_eq!(get_value("1 == 1.0"), LiteralTypes::Bool(true));
+        assert_eq!(
+            get_value("9007199254740993 > 9007199254740992.0"),
+            LiteralTypes::Bool(true)
+        );
+        assert_eq!(get_value("2 < 2.5"), LiteralTypes::Bool(true));
+        assert_eq!(number(3.0).stringify(), "3.0");
+        assert_eq!(int(3).stringify(), "3");
+    }
+
+    #[test]
+    fn big_int_and_decimal() {
+        let big = |text: &str| LiteralTypes::BigInt(BigInt::parse(text).unwrap());
+        let dec = |text: &str| LiteralTypes::Decimal(Decimal::parse(text).unwrap());
+
+        assert_eq!(get_value("2 ** 64"), big("18446744073709551616"));
+        assert_eq!(
+            get_value("9223372036854775807 + 1"),
+            big("9223372036854775808")
+        );
+        assert_eq!(
+            get_value("-(-9223372036854775807 - 1)"),
+            big("9223372036854775808")
+        );
+        assert_eq!(get_value("(2 ** 64) - (2 ** 64) + 1"), int(1));
+        assert_eq!(get_value("2 ** 64 ~/ 3 % 1000"), int(205));
+        assert_eq!(get_value("2 ** 64 > 2 ** 63"), LiteralTypes::Bool(true));
+
+        assert_eq!(get_value("0.10d + 0.2d"), dec("0.30"));
+        assert_eq!(get_value("12.50d * 3"), dec("37.50"));
+        assert_eq!(get_value("10.00d / 4"), dec("2.50"));
+        assert_eq!(get_value("1d / 3"), dec("0.33333333333333333333"));
+        assert_eq!(get_value("2d / 3"), dec("0.66666666666666666667"));
+        assert_eq!(get_value("-7.5d % 2"), dec("0.5"));
+        assert_eq!(get_value("1.5d ** 2"), dec("2.25"));
+        assert_eq!(get_value("2d ** -2"), dec("0.25"));
+        assert_eq!(get_value("1d ** 2000000000"), dec("1"));
+        assert_eq!(get_value("1 ** 4000000000"), int(1));
+        assert_eq!(get_value("1.00d == 1"), LiteralTypes::Bool(true));
+        assert_eq!(get_value("0.5d < 1"), LiteralTypes::Bool(true));
+        assert_eq!(dec("12.50").stringify(), "12.50");
+
+        for source in ["1d + 1.0", "1d / 0", "2d ** 0.5d"] {
+            let ast = Parser::new(source).expression().unwrap();
+            assert!(Interpreter::new().evaluate(&ast).is_err(), "{}", source);
+        }
+        // refused up front rather than computed for minutes
+        for source in ["1.1d ** 100000", "3 ** 4000000000", "(2 ** 64) ** 100000"] {
+            let ast = Parser::new(source).expression().unwrap();
+            match Interpreter::new().evaluate(&ast) {
+                Err(RuntimeError::Error(error)) => {
+                    assert_eq!(error.message, "Exponent is too large", "{}", source)
+                }
+                _ => panic!("{} should fail", source),
+            }
+        }
+    }
+
+    #[test]
+    fn bitwise() {
+        assert_eq!(get_value("6 & 3"), int(2));
+        assert_eq!(get_value("6 | 3"), int(7));
+        assert_eq!(get_value("6 ^ 3"), int(5));
+        assert_eq!(get_value("~5"), int(-6));
+        assert_eq!(get_value("1 << 4"), int(16));
+        assert_eq!(get_value("-16 >> 2"), int(-4));
+        // C precedence: shifts below `+`, `&` below `==`
+        assert_eq!(get_value("1 << 1 + 1"), int(4));
+        assert_eq!(get_value("1 | 2 ^ 3 & 1"), int(3));
+
+        for source in [
+            "1.5 & 1",
+            "9007199254740992.0 | 0",
+            "1 << 63",
+            "1 << -1",
+            "3 & 1 == 1",
+        ] {
+            let ast = Parser::new(source).expression().unwrap();
+            assert!(Interpreter::new().evaluate(&ast).is_err(), "{}", source);
+        }
+        let ast = Parser::new("(2 ** 64) & 1").expression().unwrap();
+        match Interpreter::new().evaluate(&ast) {
+            Err(RuntimeError::Error(error)) => assert_eq!(
+                error.message,
+                "Operand of '&' must be an integer within 64 bits, got 18446744073709551616"
+            ),
+            _ => panic!("a big int operand should fail"),
+        }
     }
 
     #[test]
@@ -296,10 +1561,7 @@ mod tests {
 
     #[test]
     fn run_stmt() {
-        let stmt = Parser::new("print \"abc\";").parse().unwrap();
-
-        let mut inter = Interpreter::new();
-        inter.interpret(&stmt);
+        assert_eq!(printed("print \"abc\";"), "\"abc\"\n");
     }
 
     #[test]
@@ -308,10 +1570,7 @@ mod tests {
         var b = 2;
         print a+b;
         "#;
-        let stmt = Parser::new(code).parse().unwrap();
-
-        let mut inter = Interpreter::new();
-        inter.interpret(&stmt);
+        assert_eq!(printed(code), "3\n");
     }
 
     #[test]
@@ -319,11 +1578,7 @@ mod tests {
         let code = r#"var b = 2;
         print b = 3;
         "#;
-        let stmt = Parser::new(code).parse().unwrap();
-        //println!("{:?}", stmt);
-
-        let mut inter = Interpreter::new();
-        inter.interpret(&stmt);
+        assert_eq!(printed(code), "3\n");
     }
 
     #[test]
@@ -348,21 +1603,18 @@ print a;
 print b;
 print c;
         "#;
-        let stmt = Parser::new(code).parse().unwrap();
-        //println!("{:?}", stmt);
-
-        let mut inter = Interpreter::new();
-        inter.interpret(&stmt);
+        let lines = [
+            "inner a", "outer b", "global c", "outer a", "outer b", "global c", "global a",
+            "global b", "global c",
+        ];
+        let expected: Vec<_> = lines.iter().map(|line| format!("\"{}\"\n", line)).collect();
+        assert_eq!(printed(code), expected.concat());
     }
 
     #[test]
     fn run_logical_stmt() {
         let code = r#"print nil or 123;"#;
-        let stmt = Parser::new(code).parse().unwrap();
-        //println!("{:?}", stmt);
-
-        let mut inter = Interpreter::new();
-        inter.interpret(&stmt);
+        assert_eq!(printed(code), "123\n");
     }
 
     #[test]
@@ -376,10 +1628,1297 @@ for (var b = 1; a < 10000; b = temp + b) {
   temp = a;
   a = b;
 }"#;
+        let fibonacci = printed(code);
+        assert!(fibonacci.starts_with("0\n1\n1\n2\n3\n5\n8\n"));
+        assert!(fibonacci.ends_with("4181\n6765\n"));
+    }
+
+    #[test]
+    fn match_expr() {
+        let source = |n: &str| {
+            format!(
+                r#"match ({n}) {{ 1 | 2 => "low", 3..5 => "mid", -1..=-1 => "neg", _ => "other" }}"#
+            )
+        };
+        assert_eq!(get_value(&source("2")), LiteralTypes::String("low".into()));
+        assert_eq!(
+            get_value(&source("4.5")),
+            LiteralTypes::String("mid".into())
+        );
+        assert_eq!(
+            get_value(&source("5")),
+            LiteralTypes::String("other".into())
+        );
+        assert_eq!(get_value(&source("-1")), LiteralTypes::String("neg".into()));
+
+        assert_eq!(get_value(r#"match ("x") { "y" => 1 }"#), LiteralTypes::Nil);
+    }
+
+    #[test]
+    fn run_match_stmt() {
+        let code = r#"
+var n = 7;
+var kind;
+match (n) {
+  0 => kind = "zero";
+  _ if n > 5 => { kind = "big"; }
+  _ => kind = "small";
+}
+"#;
+        assert_eq!(get_var(code, "kind"), LiteralTypes::String("big".into()));
+    }
+
+    #[test]
+    fn run_conditional_expr() {
+        let code = r#"
+var a = 1;
+var b = 2;
+var c = a > b ? "a" : b > 2 ? "b" : "c";
+var d = true ? a = 10 : (b = 20);
+"#;
+        assert_eq!(get_var(code, "c"), LiteralTypes::String("c".into()));
+        assert_eq!(get_var(code, "a"), int(10));
+        // only the chosen branch is evaluated
+        assert_eq!(get_var(code, "b"), int(2));
+    }
+
+    #[test]
+    fn run_compound_assign() {
+        let code = r#"
+var i = 0;
+var j = i++;
+var k = ++i;
+i += 10;
+i -= 2;
+i *= 3;
+i /= 2;
+i %= 7;
+var n = 5;
+n--;
+--n;
+"#;
+        assert_eq!(get_var(code, "j"), int(0));
+        assert_eq!(get_var(code, "k"), int(2));
+        assert_eq!(get_var(code, "i"), number(1.0));
+        assert_eq!(get_var(code, "n"), int(3));
+
+        assert!(Parser::new("1 += 2;").parse().is_err());
+        assert!(Parser::new("(a)++;").parse().is_err());
+    }
+
+    #[test]
+    fn run_list() {
+        let code = r#"
+var xs = [3, 1, 2,];
+var ys = xs;
+ys.push(10);
+xs[0] = 5;
+xs[-1] += 1;
+var last = xs.pop();
+xs.insert(0, 7);
+xs.sort();
+var sorted = xs.join(", ");
+var head = xs[1:];
+var mid = xs[-3:-1];
+var has = xs.contains(2) and !xs.contains(42);
+var size = xs.len();
+var nested = [[1, 2], [3]];
+nested[1][0] *= 2;
+var bad = [];
+try { [1].insert(3, 0); } catch (e) { bad.push(e.kind + ": " + e.message); }
+try { [1].remove(-2); } catch (e) { bad.push(e.kind + ": " + e.message); }
+"#;
+        let list = |items: Vec<LiteralTypes>| LiteralTypes::List(Rc::new(RefCell::new(items)));
+        assert_eq!(get_var(code, "last"), int(11));
+        assert_eq!(
+            get_var(code, "ys"),
+            list(vec![int(1), int(2), int(5), int(7)])
+        );
+        assert_eq!(
+            get_var(code, "sorted"),
+            LiteralTypes::String("1, 2, 5, 7".into())
+        );
+        assert_eq!(get_var(code, "head"), list(vec![int(2), int(5), int(7)]));
+        assert_eq!(get_var(code, "mid"), list(vec![int(2), int(5)]));
+        assert_eq!(get_var(code, "has"), LiteralTypes::Bool(true));
+        assert_eq!(get_var(code, "size"), int(4));
+        assert_eq!(get_var(code, "nested").stringify(), "[[1, 2], [6]]");
+        assert_eq!(
+            get_var(code, "bad").stringify(),
+            r#"["IndexError: Index 3 out of range for list of length 1.", "IndexError: Index -2 out of range for list of length 1."]"#
+        );
+
+        let cycles = r#"
+var c = [1];
+c.push(c);
+print c;
+var a = [];
+a.push(a);
+var b = [];
+b.push(b);
+print a == b;
+var d = [1];
+d.push(d);
+print c.contains(c) and c == d;
+print (c, 2) == (d, 2);
+"#;
+        assert_eq!(printed(cycles), "[1, [...]]\ntrue\ntrue\ntrue\n");
+
+        for code in [
+            "[1][1];",
+            "[][-1] = 0;",
+            "[].pop();",
+            "[1, \"a\"].sort();",
+            "[1].push();",
+        ] {
+            let stmt = Parser::new(code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_map() {
+        let code = r#"
+var m = {"a": 1, "b": 2,};
+var alias = m;
+alias["c"] = 3;
+m["a"] += 10;
+m[1] = "one";
+m[1.0] = "uno";
+m[-0.0] = "zero";
+var zero = m[0];
+var removed = m.delete("b");
+var missing = m.delete("nope");
+var keys = m.keys();
+var values = m.values();
+var has = m.has("c") and !m.has("b");
+var size = m.len();
+var same = {"x": [1], 2: nil} == {2: nil, "x": [1]};
+var empty = {};
+"#;
+        let list = |items: Vec<LiteralTypes>| LiteralTypes::List(Rc::new(RefCell::new(items)));
+        let string = |s: &str| LiteralTypes::String(s.into());
+        assert_eq!(get_var(code, "zero"), string("zero"));
+        assert_eq!(get_var(code, "removed"), int(2));
+        assert_eq!(get_var(code, "missing"), LiteralTypes::Nil);
+        assert_eq!(
+            get_var(code, "keys"),
+            list(vec![string("a"), string("c"), int(1), number(-0.0)])
+        );
+        assert_eq!(
+            get_var(code, "values"),
+            list(vec![int(11), int(3), string("uno"), string("zero")])
+        );
+        assert_eq!(get_var(code, "has"), LiteralTypes::Bool(true));
+        assert_eq!(get_var(code, "size"), int(4));
+        assert_eq!(get_var(code, "same"), LiteralTypes::Bool(true));
+        assert_eq!(
+            get_var(code, "alias").stringify(),
+            r#"{"a": 11, "c": 3, 1: "uno", -0.0: "zero"}"#
+        );
+        assert_eq!(get_var(code, "empty").stringify(), "{}");
+
+        let cycles = r#"
+var m = {};
+m["x"] = m;
+print m;
+var n = {};
+n["x"] = n;
+print m == n;
+var inside = {"xs": [m]};
+print inside;
+"#;
+        assert_eq!(
+            printed(cycles),
+            "{\"x\": {...}}\ntrue\n{\"xs\": [{\"x\": {...}}]}\n"
+        );
+
+        for code in [
+            "print {\"a\": 1}[\"b\"];",
+            "var m = {}; m[nil] = 1;",
+            "var inf = 10.0 ** 400; var m = {}; m[inf - inf] = 1;",
+            "print {}.keys(1);",
+        ] {
+            let stmt = Parser::new(code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_for_in() {
+        let code = r#"
+var xs = [1, 2, 3];
+var sum = 0;
+for (x in xs) sum += x;
+var pairs = [];
+for (i, x in ["a", "b"]) pairs.push([i, x]);
+var m = {"a": 1, "b": 2};
+var keys = "";
+for (k in m) keys += k;
+var total = 0;
+for (k, v in m) total += v;
+var chars = [];
+for (c in "héllo") chars.push(c);
+var up = [];
+for (i in 0..3) up.push(i);
+var down = [];
+for (i in 10..=0 step -5) down.push(i);
+var grown = 0;
+for (x in xs) { if (x < 3) xs.push(x + 10); grown += 1; }
+var step = 2;
+var r = 0..=6 step step;
+"#;
+        let list = |items: Vec<LiteralTypes>| LiteralTypes::List(Rc::new(RefCell::new(items)));
+        assert_eq!(get_var(code, "sum"), int(6));
+        assert_eq!(
+            get_var(code, "pairs").stringify(),
+            r#"[[0, "a"], [1, "b"]]"#
+        );
+        assert_eq!(get_var(code, "keys"), LiteralTypes::String("ab".into()));
+        assert_eq!(get_var(code, "total"), int(3));
+        assert_eq!(
+            get_var(code, "chars").stringify(),
+            r#"["h", "é", "l", "l", "o"]"#
+        );
+        assert_eq!(get_var(code, "up"), list(vec![int(0), int(1), int(2)]));
+        assert_eq!(get_var(code, "down"), list(vec![int(10), int(5), int(0)]));
+        assert_eq!(get_var(code, "grown"), int(5));
+        assert_eq!(get_var(code, "r").stringify(), "0..=6 step 2");
+
+        // the loop variable lives in the loop's own scope
+        let stmt = Parser::new("for (x in [1]) {} print x;").parse().unwrap();
+        assert!(Interpreter::new().interpret(&stmt).is_err());
+        for code in [
+            "for (x in 1) {}",
+            "for (x in 0..1.5) {}",
+            "for (x in 0..3 step 0) {}",
+        ] {
+            let stmt = Parser::new(code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_destructure() {
+        let code = r#"
+var pair = (1, "a");
+var single = (1,);
+var empty = ();
+var grouped = (1);
+var (a, b) = pair;
+var [x, y, ...rest] = [1, 2, 3, 4];
+var [only, ...none] = [5];
+var (head, ...tail) = (1, 2, 3);
+var ((p, q), r) = ((1, 2), [3]);
+(a, x) = (x, a);
+var second = pair[1];
+var same = pair == (1, "a");
+var visited = 0;
+for (v in (1, 2, 3)) visited += v;
+"#;
+        let list = |items: Vec<LiteralTypes>| LiteralTypes::List(Rc::new(RefCell::new(items)));
+        assert_eq!(get_var(code, "single").stringify(), "(1,)");
+        assert_eq!(get_var(code, "empty").stringify(), "()");
+        assert_eq!(get_var(code, "grouped"), int(1));
+        assert_eq!(get_var(code, "b"), LiteralTypes::String("a".into()));
+        assert_eq!(get_var(code, "rest"), list(vec![int(3), int(4)]));
+        assert_eq!(get_var(code, "none"), list(vec![]));
+        assert_eq!(get_var(code, "tail").stringify(), "(2, 3)");
+        assert_eq!(get_var(code, "r"), list(vec![int(3)]));
+        assert_eq!(get_var(code, "q"), int(2));
+        assert_eq!(get_var(code, "a"), int(1));
+        assert_eq!(get_var(code, "x"), int(1));
+        assert_eq!(get_var(code, "second"), LiteralTypes::String("a".into()));
+        assert_eq!(get_var(code, "same"), LiteralTypes::Bool(true));
+        assert_eq!(get_var(code, "visited"), int(6));
+
+        for code in [
+            "var (a, b) = (1, 2, 3);",
+            "var [a, b, ...c] = [1];",
+            "var (a, b) = 1;",
+            "(undefined, b) = (1, 2);",
+            "(1, 2)[0] = 3;",
+        ] {
+            let stmt = Parser::new(code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+        assert!(Parser::new("var (a, b);").parse().is_err());
+    }
+
+    #[test]
+    fn run_const() {
+        let code = r#"
+const limit = 10;
+const (low, high) = (1, 2);
+var shadowed = 0;
+{
+    const limit = 20;
+    shadowed = limit;
+}
+var total = limit + low + high;
+"#;
+        assert_eq!(get_var(code, "shadowed"), int(20));
+        assert_eq!(get_var(code, "total"), int(13));
+
+        for code in [
+            "const a;",
+            "const a = 1; a = 2;",
+            "const a = 1; a += 2;",
+            "const a = 1; a++;",
+            "const a = 1; { var b; (a, b) = (1, 2); }",
+            "const a = 1; var a = 2;",
+            "const [a, ...b] = [1]; b = [];",
+        ] {
+            assert!(Parser::new(code).parse().is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_enum() {
+        let code = r#"
+enum Color { Red, Green, }
+enum Shape { Circle(r), Rect(w, h) }
+var red = Color.Red;
+var same = red == Color.Red and red != Color.Green;
+var rect = Shape.Rect(2, 3);
+var area = rect.w * rect.h;
+var tag = rect.tag;
+var equal = Shape.Circle(1) == Shape.Circle(1.0) and Shape.Circle(1) != Shape.Circle(2);
+var name = match (Shape.Circle(5).tag) { "Circle" => "round", _ => "square" };
+"#;
+        assert_eq!(get_var(code, "same"), LiteralTypes::Bool(true));
+        assert_eq!(get_var(code, "area"), int(6));
+        assert_eq!(get_var(code, "tag"), LiteralTypes::String("Rect".into()));
+        assert_eq!(get_var(code, "equal"), LiteralTypes::Bool(true));
+        assert_eq!(get_var(code, "name"), LiteralTypes::String("round".into()));
+        assert_eq!(get_var(code, "rect").stringify(), "Shape.Rect(2, 3)");
+        assert_eq!(get_var(code, "red").stringify(), "Color.Red");
+        assert_eq!(get_var(code, "Color").stringify(), "<enum Color>");
+
+        // two declarations with the same shape are still different types
+        let code = "enum A { X } var a = A.X; enum B { X } var same = a == B.X;";
+        assert_eq!(get_var(code, "same"), LiteralTypes::Bool(false));
+
+        // a list holding a variant that holds the list
+        let cycles = r#"
+enum Box { Of(item) }
+var xs = [];
+xs.push(Box.Of(xs));
+var ys = [];
+ys.push(Box.Of(ys));
+print xs;
+print xs == ys;
+"#;
+        assert_eq!(printed(cycles), "[Box.Of([...])]\ntrue\n");
+
+        for code in [
+            "enum Color { Red } Color.Blue;",
+            "enum Shape { Circle(r) } Shape.Circle;",
+            "enum Shape { Circle(r) } Shape.Circle(1, 2);",
+            "enum Color { Red } Color.Red();",
+            "enum Color { Red } Color.Red.r;",
+        ] {
+            let stmt = Parser::new(code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+        for code in [
+            "enum Color { Red, Red }",
+            "enum Shape { Circle(tag) }",
+            "enum Color { Red } Color = 1;",
+        ] {
+            assert!(Parser::new(code).parse().is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_try() {
+        let code = r#"
+var x = "outer";
+var kind;
+var message;
+var line;
+var cleaned = false;
+try {
+    var x = "inner";
+    {
+        1 + "a";
+    }
+} catch (e) {
+    kind = e.kind;
+    message = e.message;
+    line = e.line;
+} finally {
+    cleaned = true;
+}
+var after = x;
+
+var thrown;
+try { throw {"code": 7}; } catch (e) { thrown = e.value["code"]; }
+
+var rethrown;
+try {
+    try { [][0]; } finally { rethrown = "finally ran"; }
+} catch (e) {
+    rethrown = rethrown + " then " + e.kind;
+}
+
+var order = [];
+try { order.push("try"); } catch (e) { order.push("catch"); } finally { order.push("finally"); }
+try { undefined; } catch { order.push("bare catch"); }
+"#;
+        let string = |s: &str| LiteralTypes::String(s.into());
+        assert_eq!(get_var(code, "after"), string("outer"));
+        assert_eq!(get_var(code, "kind"), string("TypeError"));
+        assert_eq!(
+            get_var(code, "message"),
+            string("Operand must be two numbers or two strings")
+        );
+        assert_eq!(get_var(code, "line"), int(10));
+        assert_eq!(get_var(code, "cleaned"), LiteralTypes::Bool(true));
+        assert_eq!(get_var(code, "thrown"), int(7));
+        assert_eq!(
+            get_var(code, "rethrown"),
+            string("finally ran then IndexError")
+        );
+        assert_eq!(
+            get_var(code, "order").stringify(),
+            r#"["try", "finally", "bare catch"]"#
+        );
+
+        // an uncaught error still leaves the interpreter in the global scope
+        let mut inter = Interpreter::new();
+        let stmt = Parser::new("var a = 1; { var a = 2; throw \"boom\"; }")
+            .parse()
+            .unwrap();
+        let error = inter.interpret(&stmt).unwrap_err();
+        assert_eq!(error.to_string(), "line:1 boom");
+        let a = Token::new(Identifier, "a".to_string(), LiteralTypes::Nil, 1);
+        assert_eq!(inter.environment.borrow().get(&a), Some(int(1)));
+
+        assert!(Parser::new("try { }").parse().is_err());
+    }
+
+    /// Write `files` into a fresh directory and run the first one as the main script.
+    fn run_files(name: &str, files: &[(&str, &str)]) -> (Interpreter, Result<(), RuntimeError>) {
+        let dir = std::env::temp_dir().join(format!("rlox-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (path, source) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+
+        let main = dir.join(files[0].0);
+        let stmt = Parser::new(files[0].1).parse().unwrap();
+        let mut inter = Interpreter::new()
+            .with_script(&main)
+            .with_search_path(vec![dir.join("vendor")]);
+        let result = inter.interpret(&stmt);
+        (inter, result)
+    }
+
+    #[test]
+    fn run_import() {
+        let main = r#"
+import "lib/util.lox" as util;
+import "lib/util.lox" as again;
+from "lib/util.lox" import double, items;
+import "shared.lox" as shared;
+items.push(1);
+var same = util == again and again.items.len() == 1;
+var four = double * 2;
+var deep = util.nested;
+var vendored = shared.name;
+"#;
+        let (inter, result) = run_files(
+            "import",
+            &[
+                ("main.lox", main),
+                (
+                    "lib/util.lox",
+                    "var items = []; var double = 2; from \"inner.lox\" import nested;",
+                ),
+                ("lib/inner.lox", "var nested = \"lib/inner\";"),
+                ("vendor/shared.lox", "var name = \"vendor\";"),
+            ],
+        );
+        result.unwrap();
+        assert_eq!(var(&inter, "same"), LiteralTypes::Bool(true));
+        assert_eq!(var(&inter, "four"), int(4));
+        assert_eq!(
+            var(&inter, "deep"),
+            LiteralTypes::String("lib/inner".into())
+        );
+        assert_eq!(
+            var(&inter, "vendored"),
+            LiteralTypes::String("vendor".into())
+        );
+        assert_eq!(var(&inter, "util").stringify(), "<module util.lox>");
+
+        let (_, result) = run_files(
+            "cycle",
+            &[
+                ("a.lox", "import \"b.lox\" as b;"),
+                ("b.lox", "import \"c.lox\" as c;"),
+                ("c.lox", "from \"b.lox\" import x;"),
+            ],
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "line:1 Circular import: b.lox -> c.lox -> b.lox."
+        );
+
+        for (name, files) in [
+            (
+                "missing",
+                [("main.lox", "import \"nope.lox\" as nope;"), ("x.lox", "")],
+            ),
+            (
+                "export",
+                [
+                    ("main.lox", "from \"x.lox\" import y;"),
+                    ("x.lox", "var z;"),
+                ],
+            ),
+            (
+                "parse",
+                [("main.lox", "import \"x.lox\" as x;"), ("x.lox", "var;")],
+            ),
+        ] {
+            let (_, result) = run_files(name, &files);
+            let Err(RuntimeError::Error(error)) = result else {
+                panic!("{}", name);
+            };
+            assert_eq!(error.kind, ErrorKind::Import, "{}", name);
+        }
+    }
+
+    #[test]
+    fn run_natives() {
+        let code = r#"
+var lengths = [len("héllo"), len([1, 2]), len({"a": 1}), len((1, 2, 3))];
+var texts = [str(1.5), str("a"), str([1, "b"]), str(nil)];
+var numbers = [num("42"), num(" -7 "), num("2.5"), num(3)];
+var types = [type(1), type(1.0), type("a"), type(nil), type(len), type((1,))];
+var elapsed = clock() >= 0;
+var passed = assert(true);
+var shown = str(len);
+
+var message;
+try { assert(1 > 2, "math is broken"); } catch (e) { message = e.kind + ": " + e.message; }
+var arity;
+try { len(); } catch (e) { arity = e.message; }
+var shadowed;
+{ var len = 3; shadowed = len; }
+"#;
+        assert_eq!(get_var(code, "lengths").stringify(), "[5, 2, 1, 3]");
+        assert_eq!(
+            get_var(code, "texts").stringify(),
+            r#"["1.5", "a", "[1, "b"]", "Nil"]"#
+        );
+        assert_eq!(get_var(code, "numbers").stringify(), "[42, -7, 2.5, 3]");
+        assert_eq!(
+            get_var(code, "types").stringify(),
+            r#"["int", "float", "string", "nil", "function", "tuple"]"#
+        );
+        assert_eq!(get_var(code, "elapsed"), LiteralTypes::Bool(true));
+        assert_eq!(get_var(code, "passed"), LiteralTypes::Nil);
+        assert_eq!(
+            get_var(code, "shown"),
+            LiteralTypes::String("<native fn len>".into())
+        );
+        assert_eq!(
+            get_var(code, "message"),
+            LiteralTypes::String("AssertionError: math is broken".into())
+        );
+        assert_eq!(
+            get_var(code, "arity"),
+            LiteralTypes::String("Expected 1 arguments but got 0.".into())
+        );
+        assert_eq!(get_var(code, "shadowed"), int(3));
+
+        // errors point at the call, not at the native
+        let stmt = Parser::new("var a = 1;\nnum(\"x\");").parse().unwrap();
+        let error = Interpreter::new().interpret(&stmt).unwrap_err();
+        assert_eq!(error.to_string(), "line:2 Can't convert 'x' to a number.");
+
+        let stmt = Parser::new("exit(3); print 1;").parse().unwrap();
+        assert!(matches!(
+            Interpreter::new().interpret(&stmt),
+            Err(RuntimeError::Exit(3))
+        ));
+        let stmt = Parser::new("try { exit(); } catch { }").parse().unwrap();
+        assert!(matches!(
+            Interpreter::new().interpret(&stmt),
+            Err(RuntimeError::Exit(0))
+        ));
+
+        // natives are globals, visible to modules but not exported by them
+        let (_, result) = run_files(
+            "natives",
+            &[
+                (
+                    "main.lox",
+                    "from \"m.lox\" import n; from \"m.lox\" import len;",
+                ),
+                ("m.lox", "var n = len(\"abc\");"),
+            ],
+        );
+        assert!(result.is_err());
+
+        for code in [
+            "len(1);",
+            "len(1, 2);",
+            "num(nil);",
+            "num(\"1e\");",
+            "exit(\"1\");",
+            "assert();",
+            "1();",
+            "nil.len();",
+            "(1, 2).x();",
+        ] {
+            let stmt = Parser::new(code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_math() {
+        let code = r#"
+import "math" as math;
+from "math" import sqrt, pi;
+var roots = [sqrt(16), math.sqrt(2) * math.sqrt(2) > 1.99];
+var rounded = [math.floor(2.7), math.ceil(2.1), math.round(-2.5), math.floor(3), math.abs(-4), math.abs(-1.5)];
+var extremes = [math.min(3, 1.5, 2), math.max([4, 9, 2]), math.max(1)];
+var trig = [math.sin(0), math.cos(0), math.atan2(1, 1) * 4 == pi, math.log(math.e), math.log(8, 2)];
+var powers = [math.pow(2, 10), math.exp(0), math.pow(-8, 2)];
+
+math.seed(7);
+var first = [math.random(), math.random_int(1, 6), math.random_int(-3, -3)];
+math.seed(7);
+var again = [math.random(), math.random_int(1, 6), math.random_int(-3, -3)];
+var same = first == again;
+var unit = 0 <= first[0] and first[0] < 1;
+
+var caught;
+try { math.sqrt(-1); } catch (e) { caught = e.kind; }
+var too_big = [];
+try { math.seed(2 ** 70); } catch (e) { too_big.push(e.message); }
+try { math.random_int(0, 2 ** 70); } catch (e) { too_big.push(e.message); }
+"#;
+        assert_eq!(get_var(code, "roots").stringify(), "[4.0, true]");
+        assert_eq!(
+            get_var(code, "rounded").stringify(),
+            "[2, 3, -3, 3, 4, 1.5]"
+        );
+        assert_eq!(get_var(code, "extremes").stringify(), "[1.5, 9, 1]");
+        assert_eq!(
+            get_var(code, "trig").stringify(),
+            "[0.0, 1.0, true, 1.0, 3.0]"
+        );
+        assert_eq!(get_var(code, "powers").stringify(), "[1024.0, 1.0, 64.0]");
+        assert_eq!(get_var(code, "same"), LiteralTypes::Bool(true));
+        assert_eq!(get_var(code, "unit"), LiteralTypes::Bool(true));
+        assert_eq!(
+            get_var(code, "caught"),
+            LiteralTypes::String("ArithmeticError".into())
+        );
+        assert_eq!(
+            get_var(code, "too_big").stringify(),
+            r#"["seed must fit in 64 bits, got 1180591620717411303424.", "random_int bounds must fit in 64 bits."]"#
+        );
+
+        for code in [
+            "math.sqrt(-1);",
+            "math.log(0);",
+            "math.log(8, 1);",
+            "math.pow(-8, 0.5);",
+            "math.pow(0, -1);",
+            "math.exp(1000);",
+            "math.floor(10.0 ** 400);",
+            "math.min();",
+            "math.min([]);",
+            "math.max(1, \"a\");",
+            "math.sqrt(\"4\");",
+            "math.random_int(2, 1);",
+            "math.seed(1.5);",
+            "math.tau;",
+        ] {
+            let code = format!("import \"math\" as math; {}", code);
+            let stmt = Parser::new(&code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_strings() {
+        let code = r#"
+var s = "  Héllo, wörld  ";
+var t = s.trim();
+var sizes = [t.len(), s.trim_start().len(), s.trim_end().len()];
+var parts = [t.substr(1, 5), t.substr(-5), t[1], t[-1], t[:5], t[7:]];
+var found = [t.index_of("ö"), t.index_of("l", 4), t.index_of("x"), t.contains("llo"), t.starts_with("Hé"), t.ends_with("d")];
+var cases = [t.upper(), t.lower()];
+var pieces = ["a,b,,c".split(","), " x  y ".split(), "-".join(["a", 1, true]), ", ".join((1, 2))];
+var edits = [t.replace("l", "L"), "ab".repeat(3), "ab".repeat(0)];
+var chars = "añ😀".chars();
+var codes = ["A".char_code(), "añ".char_code(1), from_char_code(128512), from_char_code(241)];
+var huge;
+try { "ab".repeat(100000000000); } catch (e) { huge = e.message; }
+var outside;
+try { "a".char_code(5); } catch (e) { outside = e.kind + ": " + e.message; }
+"#;
+        assert_eq!(
+            get_var(code, "t"),
+            LiteralTypes::String("Héllo, wörld".into())
+        );
+        assert_eq!(get_var(code, "sizes").stringify(), "[12, 14, 14]");
+        assert_eq!(
+            get_var(code, "parts").stringify(),
+            r#"["éllo", "wörld", "é", "d", "Héllo", "wörld"]"#
+        );
+        assert_eq!(
+            get_var(code, "found").stringify(),
+            "[8, 10, -1, true, true, true]"
+        );
+        assert_eq!(
+            get_var(code, "cases").stringify(),
+            r#"["HÉLLO, WÖRLD", "héllo, wörld"]"#
+        );
+        assert_eq!(
+            get_var(code, "pieces").stringify(),
+            r#"[["a", "b", "", "c"], ["x", "y"], "a-1-true", "1, 2"]"#
+        );
+        assert_eq!(
+            get_var(code, "edits").stringify(),
+            r#"["HéLLo, wörLd", "ababab", ""]"#
+        );
+        assert_eq!(get_var(code, "chars").stringify(), r#"["a", "ñ", "😀"]"#);
+        assert_eq!(
+            get_var(code, "codes").stringify(),
+            r#"[65, 241, "😀", "ñ"]"#
+        );
+        assert_eq!(
+            get_var(code, "outside"),
+            LiteralTypes::String("IndexError: Index 5 out of range for string of length 1.".into())
+        );
+        assert_eq!(
+            get_var(code, "huge"),
+            LiteralTypes::String(
+                "Can't repeat a string 100000000000 times, the result would be over 10000000 chars."
+                    .into()
+            )
+        );
+
+        for code in [
+            "\"a\".nope();",
+            "\"a\".upper(1);",
+            "\"a\".contains(1);",
+            "\"a\".split(\"\");",
+            "\"a\".repeat(-1);",
+            "\"a\".substr(\"1\");",
+            "\"\".char_code();",
+            "\"ab\"[2];",
+            "\"ab\"[0] = \"c\";",
+            "\",\".join(1);",
+            "from_char_code(55296);",
+            "from_char_code(-1);",
+        ] {
+            let stmt = Parser::new(code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_fs() {
+        let dir = std::env::temp_dir().join(format!("rlox-fs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for sub in ["rw", "ro", "outside"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        fs::write(dir.join("ro/notes.txt"), "one\ntwo\n").unwrap();
+        fs::write(dir.join("outside/secret.txt"), "shh").unwrap();
+
+        let code = r#"
+import "fs" as fs;
+fs.write_text(rw + "/out.txt", "hello");
+fs.append(rw + "/out.txt", ", world");
+fs.append(rw + "/log.txt", "a");
+var text = fs.read_text(rw + "/out.txt");
+var lines = fs.read_lines(ro + "/notes.txt");
+var listed = fs.list_dir(rw);
+fs.remove(rw + "/log.txt");
+var after = [fs.exists(rw + "/log.txt"), fs.exists(rw + "/out.txt")];
+
+var denied = [];
+var attempts = [
+    (fs.read_text, ro + "/../outside/secret.txt"),
+    (fs.exists, ro + "/../outside"),
+    (fs.list_dir, "/"),
+    (fs.remove, ro + "/notes.txt"),
+];
+for (attempt in attempts) {
+    var (f, path) = attempt;
+    try { f(path); } catch (e) { denied.push(e.kind); }
+}
+try { fs.write_text(ro + "/new.txt", "x"); } catch (e) { denied.push(e.message == "Not allowed to write '" + ro + "/new.txt'."); }
+var missing;
+try { fs.read_text(rw + "/nope.txt"); } catch (e) { missing = e.kind; }
+"#;
+        let path = |sub: &str| LiteralTypes::String(dir.join(sub).display().to_string());
+        let stmt = Parser::new(code).parse().unwrap();
+        let mut inter = Interpreter::new()
+            .with_fs_root(&dir.join("rw"), Access::ReadWrite)
+            .with_fs_root(&dir.join("ro"), Access::ReadOnly);
+        inter
+            .environment
+            .borrow_mut()
+            .define("rw".into(), path("rw"));
+        inter
+            .environment
+            .borrow_mut()
+            .define("ro".into(), path("ro"));
+        inter.interpret(&stmt).unwrap();
+
+        assert_eq!(
+            var(&inter, "text"),
+            LiteralTypes::String("hello, world".into())
+        );
+        assert_eq!(var(&inter, "lines").stringify(), r#"["one", "two"]"#);
+        assert_eq!(
+            var(&inter, "listed").stringify(),
+            r#"["log.txt", "out.txt"]"#
+        );
+        assert_eq!(var(&inter, "after").stringify(), "[false, true]");
+        assert_eq!(
+            var(&inter, "denied").stringify(),
+            r#"["PermissionError", "PermissionError", "PermissionError", "PermissionError", true]"#
+        );
+        assert_eq!(
+            var(&inter, "missing"),
+            LiteralTypes::String("IOError".into())
+        );
+        assert!(!dir.join("ro/new.txt").exists());
+        assert!(dir.join("ro/notes.txt").exists());
+
+        // without any roots, nothing is reachable
+        let stmt = Parser::new("import \"fs\" as fs; fs.exists(\".\");")
+            .parse()
+            .unwrap();
+        assert!(Interpreter::new().interpret(&stmt).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn run_fs_escapes() {
+        let dir = std::env::temp_dir().join(format!("rlox-fs-escapes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for sub in ["rw", "outside"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        // a dangling link inside the root, pointing out of it
+        std::os::unix::fs::symlink(dir.join("outside/planted.txt"), dir.join("rw/link")).unwrap();
+
+        let code = r#"
+import "fs" as fs;
+var denied = [];
+for (path in [rw + "/link", rw + "/."]) {
+    try { fs.write_text(path, "x"); } catch (e) { denied.push(e.kind); }
+}
+try { fs.remove(rw); } catch (e) { denied.push(e.kind); }
+"#;
+        let stmt = Parser::new(code).parse().unwrap();
+        let mut inter = Interpreter::new().with_fs_root(&dir.join("rw"), Access::ReadWrite);
+        let rw = LiteralTypes::String(dir.join("rw").display().to_string());
+        inter.environment.borrow_mut().define("rw".into(), rw);
+        inter.interpret(&stmt).unwrap();
+
+        let denied = var(&inter, "denied");
+        // writing to the root itself is left to the OS, which refuses a directory
+        assert_eq!(
+            denied.stringify(),
+            r#"["PermissionError", "IOError", "PermissionError"]"#
+        );
+        assert!(!dir.join("outside/planted.txt").exists());
+        assert!(dir.join("rw").is_dir());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn run_process() {
+        let code = r#"
+var all = args();
+var home = env("HOME");
+var unset = env("UNSET");
+var first = read_line();
+var name = input();
+var rest = read_all_stdin();
+var done = [read_line(), read_all_stdin()];
+"#;
+        let stmt = Parser::new(code).parse().unwrap();
+        let mut inter = Interpreter::new()
+            .with_args(vec!["-v".into(), "in.txt".into()])
+            .with_env(|name| (name == "HOME").then(|| "/home/lox".to_string()))
+            .with_stdin("first\r\nAda\nthe\nrest".as_bytes());
+        inter.interpret(&stmt).unwrap();
+
+        assert_eq!(var(&inter, "all").stringify(), r#"["-v", "in.txt"]"#);
+        assert_eq!(
+            var(&inter, "home"),
+            LiteralTypes::String("/home/lox".into())
+        );
+        assert_eq!(var(&inter, "unset"), LiteralTypes::Nil);
+        assert_eq!(var(&inter, "first"), LiteralTypes::String("first".into()));
+        assert_eq!(var(&inter, "name"), LiteralTypes::String("Ada".into()));
+        assert_eq!(
+            var(&inter, "rest"),
+            LiteralTypes::String("the\nrest".into())
+        );
+        assert_eq!(var(&inter, "done").stringify(), r#"[Nil, ""]"#);
+
+        for code in ["env(1);", "args(1);", "read_line(\"x\");"] {
+            let stmt = Parser::new(code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_json() {
+        // Lox strings have no escapes, so the JSON texts come in as variables
+        let code = r#"
+import "json" as json;
+var config = json.parse(text);
+var fields = [config["name"], config["version"], config["ratio"], config["tags"][1], config["big"]];
+var compact = json.stringify({"a": [1, 2.0, (3, "x")], 1: nil, "e": {}, "l": []});
+var pretty = json.stringify({"a": [1, {"b": false}], "c": "d" + from_char_code(34)}, 2);
+var again = json.parse(json.stringify(config)) == config;
+
+var where;
+try { json.parse(broken); } catch (e) { where = e.kind + ": " + e.message; }
+var cycle = [1];
+cycle.push(cycle);
+var why;
+try { json.stringify(cycle); } catch (e) { why = e.message; }
+"#;
+        let text = r#"{"name": "lox", "version": 2, "ratio": 0.5, "tags": ["a", null, true],
+            "big": 123456789012345678901234567890}"#;
+        let stmt = Parser::new(code).parse().unwrap();
+        let mut inter = Interpreter::new();
+        for (name, value) in [("text", text), ("broken", "{\n  \"a\": }")] {
+            let value = LiteralTypes::String(value.into());
+            inter.environment.borrow_mut().define(name.into(), value);
+        }
+        inter.interpret(&stmt).unwrap();
+
+        assert_eq!(
+            var(&inter, "fields").stringify(),
+            r#"["lox", 2, 0.5, Nil, 123456789012345678901234567890]"#
+        );
+        assert_eq!(
+            var(&inter, "compact"),
+            LiteralTypes::String(r#"{"a":[1,2.0,[3,"x"]],"1":null,"e":{},"l":[]}"#.into())
+        );
+        assert_eq!(
+            var(&inter, "pretty"),
+            LiteralTypes::String(
+                "{\n  \"a\": [\n    1,\n    {\n      \"b\": false\n    }\n  ],\n  \"c\": \"d\\\"\"\n}"
+                    .into()
+            )
+        );
+        assert_eq!(var(&inter, "again"), LiteralTypes::Bool(true));
+        assert_eq!(
+            var(&inter, "where"),
+            LiteralTypes::String(
+                "ParseError: Expected a JSON value but found '}' at line 2, column 8.".into()
+            )
+        );
+        assert_eq!(
+            var(&inter, "why"),
+            LiteralTypes::String("Can't convert a value that contains itself to JSON.".into())
+        );
+
+        for code in [
+            "json.stringify(len);",
+            "json.stringify(json);",
+            "json.stringify(10.0 ** 400);",
+            "json.stringify({(1, 2): 3});",
+            "json.stringify(1, 11);",
+            "json.stringify(1, 1.5);",
+            "json.parse(1);",
+            "json.parse(\"[1\");",
+        ] {
+            let code = format!("import \"json\" as json; {}", code);
+            let stmt = Parser::new(&code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_re() {
+        let code = r#"
+import "re" as re;
+from "re" import compile;
+var date = re.match("(\d+)-(\d+)(-(\d+))?", "on 2024-05!");
+var span = (date["text"], date["start"], date["end"], date["groups"]);
+var none = re.match("x", "abc");
+var words = re.find_all("\w+", "héllo, wörld 42");
+var keys = re.find_all("(\w+)=\d", "a=1 b=2");
+var pairs = re.find_all("(\w+)=(\d)", "a=1 b=2");
+var swapped = re.replace("(\w+)=(\d)", "a=1 b=2", "$2=$1 $$");
+var first = re.replace("o", "foo boo", "0", 1);
+var parts = re.split(",\s*", "a, b,c,,d");
+var chars = re.split("", "abc");
+
+var digits = compile("\d+");
+var reused = (digits.find_all("1 22 333"), digits.pattern, digits.groups, str(digits));
+var same = digits == compile("\d+");
+var linear = re.match("(a*)*b", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+
+var where;
+try { re.compile("a(b"); } catch (e) { where = e.kind + ": " + e.message; }
+"#;
+        let stmt = Parser::new(code).parse().unwrap();
+        let mut inter = Interpreter::new();
+        inter.interpret(&stmt).unwrap();
+
+        assert_eq!(
+            var(&inter, "span").stringify(),
+            r#"("2024-05", 3, 10, ["2024", "05", Nil, Nil])"#
+        );
+        assert_eq!(var(&inter, "none"), LiteralTypes::Nil);
+        assert_eq!(
+            var(&inter, "words").stringify(),
+            r#"["héllo", "wörld", "42"]"#
+        );
+        assert_eq!(var(&inter, "keys").stringify(), r#"["a", "b"]"#);
+        assert_eq!(
+            var(&inter, "pairs").stringify(),
+            r#"[("a", "1"), ("b", "2")]"#
+        );
+        assert_eq!(
+            var(&inter, "swapped"),
+            LiteralTypes::String("1=a $ 2=b $".into())
+        );
+        assert_eq!(var(&inter, "first"), LiteralTypes::String("f0o boo".into()));
+        assert_eq!(
+            var(&inter, "parts").stringify(),
+            r#"["a", "b", "c", "", "d"]"#
+        );
+        assert_eq!(var(&inter, "chars").stringify(), r#"["a", "b", "c"]"#);
+        assert_eq!(
+            var(&inter, "reused").stringify(),
+            r#"(["1", "22", "333"], "\d+", 0, "<regex /\d+/>")"#
+        );
+        assert_eq!(var(&inter, "same"), LiteralTypes::Bool(true));
+        assert_eq!(var(&inter, "linear"), LiteralTypes::Nil);
+        assert_eq!(
+            var(&inter, "where"),
+            LiteralTypes::String("ParseError: Missing ')' at position 3.".into())
+        );
+
+        for code in [
+            "re.match(1, \"a\");",
+            "re.match(\"a\", 1);",
+            "re.match(\"a\");",
+            "re.replace(\"a\", \"a\", 1);",
+            "re.replace(\"a\", \"a\", \"$2\");",
+            "re.replace(\"a\", \"a\", \"b\", -1);",
+            "re.compile(\"a\").search(\"a\");",
+            "re.compile(\"a\").flags;",
+            "re.compile(\"a{2,1}\");",
+        ] {
+            let code = format!("import \"re\" as re; {}", code);
+            let stmt = Parser::new(&code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_time() {
+        let code = r#"
+import "time" as time;
+var start = time.now();
+var tick = time.monotonic();
+time.sleep(1500);
+var waited = (time.now() - start, time.monotonic() - tick, clock());
+
+var d = time.date(start, -300);
+var parts = (d["year"], d["month"], d["day"], d["hour"], d["minute"], d["weekday"], d["yearday"]);
+var back = time.timestamp(d) == start;
+var noon = time.timestamp({"year": 2024, "month": 2, "day": 29, "hour": 12});
+var stamp = time.format(start, "%A %B %d %Y %H:%M:%S.%f %z", 60);
+var iso = time.format(time.now(), "%FT%TZ");
+var parsed = time.parse("2024-02-29 07:00 -0500", "%Y-%m-%d %H:%M %z") == noon;
+var local = time.parse("29/02/24 1:00 PM", "%d/%m/%y %I:%M %p", 60) == noon;
+
+var where;
+try { time.parse("2024-13-01", "%F"); } catch (e) { where = e.kind + ": " + e.message; }
+"#;
+        let stmt = Parser::new(code).parse().unwrap();
+        // 2024-01-02T03:04:05.678Z
+        let clock = Rc::new(FakeClock::new(1_704_164_645_678));
+        let mut inter = Interpreter::new().with_clock(clock.clone());
+        inter.interpret(&stmt).unwrap();
+
+        assert_eq!(
+            var(&inter, "waited").stringify(),
+            "(1500, 1500.0, 1704164647.178)"
+        );
+        assert_eq!(clock.now(), 1_704_164_647_178);
+        assert_eq!(
+            var(&inter, "parts").stringify(),
+            "(2024, 1, 1, 22, 4, 1, 1)"
+        );
+        assert_eq!(var(&inter, "back"), LiteralTypes::Bool(true));
+        assert_eq!(var(&inter, "noon"), LiteralTypes::Int(1_709_208_000_000));
+        assert_eq!(
+            var(&inter, "stamp"),
+            LiteralTypes::String("Tuesday January 02 2024 04:04:05.678 +0100".into())
+        );
+        assert_eq!(
+            var(&inter, "iso"),
+            LiteralTypes::String("2024-01-02T03:04:07Z".into())
+        );
+        assert_eq!(var(&inter, "parsed"), LiteralTypes::Bool(true));
+        assert_eq!(var(&inter, "local"), LiteralTypes::Bool(true));
+        assert_eq!(
+            var(&inter, "where"),
+            LiteralTypes::String("ParseError: The month must be between 1 and 12, got 13.".into())
+        );
+
+        for code in [
+            "time.sleep(-1);",
+            "time.sleep(\"1\");",
+            "time.date(\"now\");",
+            "time.date(0, 1440);",
+            "time.date(10 ** 18);",
+            "time.format(0, \"%Q\");",
+            "time.format(0, \"100%\");",
+            "time.timestamp({\"year\": 2023, \"month\": 2, \"day\": 29});",
+            "time.timestamp({\"month\": 1, \"day\": 1});",
+            "time.parse(\"12\", \"%H:%M\");",
+        ] {
+            let code = format!("import \"time\" as time; {}", code);
+            let stmt = Parser::new(&code).parse().unwrap();
+            let mut inter = Interpreter::new().with_clock(Rc::new(FakeClock::new(0)));
+            assert!(inter.interpret(&stmt).is_err(), "{}", code);
+        }
+
+        // refused before it reaches the system clock, which can't sleep that long
+        let code = "import \"time\" as time; time.sleep(10.0 ** 30);";
         let stmt = Parser::new(code).parse().unwrap();
-        //println!("{:?}", stmt);
+        match Interpreter::new().interpret(&stmt) {
+            Err(RuntimeError::Error(error)) => assert_eq!(
+                error.message,
+                "Can't sleep longer than 8640000000000000 ms."
+            ),
+            _ => panic!("an oversized sleep should fail"),
+        }
+    }
 
+    #[test]
+    fn run_format() {
+        let code = r#"
+var rows = [("apples", 3, 1.5), ("kiwis", 12, 0.25)];
+var table = [];
+for (row in rows) {
+    var (name, count, price) = row;
+    table.push(format("{:<8}|{:>4}|{:>8.2}", name, count, count * price));
+}
+var named = format("{who} owes {amount:+.2f} ({0:#x})", 255, {"who": "Al", "amount": 3});
+var sci = format("{:.3e} {:^9} {:08.1f}", 123456, "mid", -2.25);
+var dec = format("{:.1}", 2.25d);
+
+var where;
+try { format("total: {:>8.q}", 1); } catch (e) { where = e.kind + ": " + e.message; }
+"#;
+        let stmt = Parser::new(code).parse().unwrap();
         let mut inter = Interpreter::new();
-        inter.interpret(&stmt);
+        inter.interpret(&stmt).unwrap();
+
+        assert_eq!(
+            var(&inter, "table").stringify(),
+            r#"["apples  |   3|    4.50", "kiwis   |  12|    3.00"]"#
+        );
+        assert_eq!(
+            var(&inter, "named"),
+            LiteralTypes::String("Al owes +3.00 (0xff)".into())
+        );
+        assert_eq!(
+            var(&inter, "sci"),
+            LiteralTypes::String("1.235e+05    mid    -00002.2".into())
+        );
+        assert_eq!(var(&inter, "dec"), LiteralTypes::String("2.2".into()));
+        assert_eq!(
+            var(&inter, "where"),
+            LiteralTypes::String(
+                "ParseError: Expected a precision after '.' at position 12.".into()
+            )
+        );
+
+        for code in [
+            "format();",
+            "format(1);",
+            "format(\"{}\");",
+            "format(\"{x}\", {\"y\": 1});",
+            "format(\"{:d}\", 1.5);",
+            "format(\"{:+}\", \"a\");",
+        ] {
+            let stmt = Parser::new(code).parse().unwrap();
+            assert!(Interpreter::new().interpret(&stmt).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn run_output_sinks() {
+        let code = r#"
+print "héllo";
+print 1;
+var answer = input("name? ");
+print answer;
+match (3) { 1 => print "never"; }
+"#;
+        let stmt = Parser::new(code).parse().unwrap();
+        let out = Rc::new(RefCell::new(Vec::new()));
+        let err = Rc::new(RefCell::new(std::string::String::new()));
+        let (out_sink, err_sink) = (out.clone(), err.clone());
+        let mut inter = Interpreter::new()
+            .with_stdin("Al\n".as_bytes())
+            .with_stdout_fn(move |text| out_sink.borrow_mut().push(text.to_string()))
+            .with_stderr_fn(move |text| err_sink.borrow_mut().push_str(text));
+        inter.interpret(&stmt).unwrap();
+
+        // a line per call, and the prompt on its own when `input()` shows it
+        assert_eq!(*out.borrow(), ["\"héllo\"\n", "1\n", "name? ", "\"Al\"\n"]);
+        assert_eq!(*err.borrow(), "[Line 6] Warning: No match arm matched 3.\n");
+
+        // `run_in` reports parse and runtime errors to the interpreter's stderr
+        for (code, status, message) in [
+            ("print 1", 65, "Expect ';' after value."),
+            ("print 1 / \"a\";", 70, "line:1 "),
+        ] {
+            let err = Rc::new(RefCell::new(std::string::String::new()));
+            let sink = err.clone();
+            let mut inter =
+                Interpreter::new().with_stderr_fn(move |text| sink.borrow_mut().push_str(text));
+            assert_eq!(crate::parser::run_in(&mut inter, code), status);
+            assert!(err.borrow().contains(message), "{}", err.borrow());
+        }
+
+        struct Closed;
+        impl Write for Closed {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let stmt = Parser::new("print 1;").parse().unwrap();
+        match Interpreter::new().with_stdout(Closed).interpret(&stmt) {
+            Err(RuntimeError::Error(error)) => assert_eq!(error.kind, ErrorKind::Io),
+            _ => panic!("writing to a closed stdout should fail"),
+        }
     }
 }
@@ -0,0 +1,120 @@
+//! Range values and the iteration behind `for (x in xs)`.
+
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use crate::lexer::LiteralTypes;
+
+/// `start..end` or `start..=end`, counting by `step`, which is never zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub step: i64,
+    pub inclusive: bool,
+}
+
+impl Range {
+    pub fn new(start: i64, end: i64, step: i64, inclusive: bool) -> Result<Self, String> {
+        if step == 0 {
+            return Err("Range step can't be 0.".to_string());
+        }
+        Ok(Self {
+            start,
+            end,
+            step,
+            inclusive,
+        })
+    }
+
+    fn contains_step(&self, value: i64) -> bool {
+        match (self.step > 0, self.inclusive) {
+            (true, true) => value <= self.end,
+            (true, false) => value < self.end,
+            (false, true) => value >= self.end,
+            (false, false) => value > self.end,
+        }
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = if self.inclusive { "..=" } else { ".." };
+        write!(f, "{}{}{}", self.start, op, self.end)?;
+        if self.step != 1 {
+            write!(f, " step {}", self.step)?;
+        }
+        Ok(())
+    }
+}
+
+/// A loop in progress over an iterable value.
+pub enum Iter {
+    /// Lists are walked by position, so elements pushed during the loop are visited.
+    List {
+        list: Rc<RefCell<Vec<LiteralTypes>>>,
+        at: usize,
+    },
+    /// Maps, tuples and strings are walked over a snapshot taken when the loop starts.
+    Entries(std::vec::IntoIter<(LiteralTypes, LiteralTypes)>),
+    Range {
+        range: Range,
+        next: Option<i64>,
+        at: usize,
+    },
+}
+
+impl Iter {
+    pub fn new(value: &LiteralTypes) -> Result<Self, String> {
+        let position = |at: usize| LiteralTypes::Int(at as i64);
+        match value {
+            LiteralTypes::List(list) => Ok(Iter::List {
+                list: list.clone(),
+                at: 0,
+            }),
+            LiteralTypes::Map(map) => {
+                Ok(Iter::Entries(map.borrow().entries().to_vec().into_iter()))
+            }
+            LiteralTypes::Tuple(items) => Ok(Iter::Entries(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(at, item)| (position(at), item.clone()))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            )),
+            LiteralTypes::String(str) => Ok(Iter::Entries(
+                str.chars()
+                    .enumerate()
+                    .map(|(at, c)| (position(at), LiteralTypes::String(c.to_string())))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            )),
+            LiteralTypes::Range(range) => Ok(Iter::Range {
+                range: range.clone(),
+                next: Some(range.start),
+                at: 0,
+            }),
+            _ => Err(format!("Can't iterate over a {}.", value.type_name())),
+        }
+    }
+
+    /// The next `(key, value)` pair. Keys are positions, except for maps where they
+    /// are the entry keys.
+    pub fn next(&mut self) -> Option<(LiteralTypes, LiteralTypes)> {
+        match self {
+            Iter::List { list, at } => {
+                let item = list.borrow().get(*at)?.clone();
+                *at += 1;
+                Some((LiteralTypes::Int(*at as i64 - 1), item))
+            }
+            Iter::Entries(entries) => entries.next(),
+            Iter::Range { range, next, at } => {
+                let value = next.filter(|value| range.contains_step(*value))?;
+                // stop instead of overflowing past the ends of i64
+                *next = value.checked_add(range.step);
+                *at += 1;
+                Some((LiteralTypes::Int(*at as i64 - 1), LiteralTypes::Int(value)))
+            }
+        }
+    }
+}
@@ -0,0 +1,540 @@
+//! The `json` module: `json.parse(text)` and `json.stringify(value, indent?)`.
+//!
+//! Objects become maps and arrays become lists. Whole numbers parse as ints and the rest
+//! as floats, so `1` and `1.0` round-trip as they were written.
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{lexer::LiteralTypes, number::BigInt};
+
+use super::{
+    callable::{Callable, NativeError},
+    exception::ErrorKind,
+    Map,
+};
+
+/// How deep arrays and objects may nest, in both directions, before we give up rather
+/// than overflow the stack.
+const MAX_DEPTH: usize = 512;
+
+pub fn exports() -> Vec<(&'static str, LiteralTypes)> {
+    let parse = Callable::native("parse", 1..=1, |_, args| match &args[0] {
+        LiteralTypes::String(text) => {
+            parse(text).map_err(|message| NativeError::Error(ErrorKind::Parse, message))
+        }
+        other => Err(NativeError::type_error(format!(
+            "Can only parse a string, got {}.",
+            other.type_name()
+        ))),
+    });
+    let stringify = Callable::native("stringify", 1..=2, |_, args| {
+        let indent = match args.get(1) {
+            None | Some(LiteralTypes::Nil) => None,
+            Some(LiteralTypes::Int(width)) if (0..=10).contains(width) => {
+                Some(" ".repeat(*width as usize))
+            }
+            Some(LiteralTypes::String(indent)) => Some(indent.clone()),
+            Some(other) => {
+                return Err(NativeError::type_error(format!(
+                    "Indent must be an int from 0 to 10 or a string, got {}.",
+                    other.stringify()
+                )))
+            }
+        };
+        let mut writer = Writer {
+            out: String::new(),
+            indent,
+            depth: 0,
+            seen: Vec::new(),
+        };
+        writer.value(&args[0]).map_err(NativeError::type_error)?;
+        Ok(LiteralTypes::String(writer.out))
+    });
+    vec![("parse", parse), ("stringify", stringify)]
+}
+
+/// Parse a complete JSON document. Errors say where, as a 1-based line and column.
+pub fn parse(text: &str) -> Result<LiteralTypes, String> {
+    let mut reader = Reader {
+        text,
+        at: 0,
+        line: 1,
+        column: 1,
+        depth: 0,
+    };
+    reader.whitespace();
+    let value = reader.value()?;
+    reader.whitespace();
+    if reader.peek().is_some() {
+        return Err(reader.error("Unexpected data after the JSON value"));
+    }
+    Ok(value)
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    /// Byte offset of the next char.
+    at: usize,
+    line: usize,
+    column: usize,
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.at..].chars().next()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.at += char.len_utf8();
+        if char == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(char)
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at line {}, column {}.", message, self.line, self.column)
+    }
+
+    fn expected(&self, what: &str) -> String {
+        match self.peek() {
+            Some(found) => self.error(&format!("Expected {} but found '{}'", what, found)),
+            None => self.error(&format!("Expected {} but the text ended", what)),
+        }
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.advance();
+        }
+    }
+
+    fn value(&mut self) -> Result<LiteralTypes, String> {
+        match self.peek() {
+            Some('{') => self.nested(Self::object),
+            Some('[') => self.nested(Self::array),
+            Some('"') => Ok(LiteralTypes::String(self.string()?)),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.keyword("true", LiteralTypes::Bool(true)),
+            Some('f') => self.keyword("false", LiteralTypes::Bool(false)),
+            Some('n') => self.keyword("null", LiteralTypes::Nil),
+            _ => Err(self.expected("a JSON value")),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<LiteralTypes, String>,
+    ) -> Result<LiteralTypes, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("Too deeply nested"));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn keyword(&mut self, word: &str, value: LiteralTypes) -> Result<LiteralTypes, String> {
+        if !self.text[self.at..].starts_with(word) {
+            return Err(self.expected("a JSON value"));
+        }
+        for _ in 0..word.len() {
+            self.advance();
+        }
+        Ok(value)
+    }
+
+    /// `{ "key": value, ... }`
+    fn object(&mut self) -> Result<LiteralTypes, String> {
+        self.advance();
+        let mut map = Map::default();
+        self.whitespace();
+        if self.peek() == Some('}') {
+            self.advance();
+            return Ok(LiteralTypes::Map(Rc::new(RefCell::new(map))));
+        }
+        loop {
+            self.whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.expected("a string key"));
+            }
+            let key = self.string()?;
+            self.whitespace();
+            if self.advance_if(':').is_none() {
+                return Err(self.expected("':'"));
+            }
+            self.whitespace();
+            let value = self.value()?;
+            map.insert(LiteralTypes::String(key), value)?;
+            self.whitespace();
+            match self.advance_if(',').or_else(|| self.advance_if('}')) {
+                Some(',') => {}
+                Some(_) => return Ok(LiteralTypes::Map(Rc::new(RefCell::new(map)))),
+                None => return Err(self.expected("',' or '}'")),
+            }
+        }
+    }
+
+    /// `[ value, ... ]`
+    fn array(&mut self) -> Result<LiteralTypes, String> {
+        self.advance();
+        let mut items = Vec::new();
+        self.whitespace();
+        if self.peek() == Some(']') {
+            self.advance();
+            return Ok(LiteralTypes::List(Rc::new(RefCell::new(items))));
+        }
+        loop {
+            self.whitespace();
+            items.push(self.value()?);
+            self.whitespace();
+            match self.advance_if(',').or_else(|| self.advance_if(']')) {
+                Some(',') => {}
+                Some(_) => return Ok(LiteralTypes::List(Rc::new(RefCell::new(items)))),
+                None => return Err(self.expected("',' or ']'")),
+            }
+        }
+    }
+
+    fn advance_if(&mut self, expected: char) -> Option<char> {
+        if self.peek() != Some(expected) {
+            return None;
+        }
+        self.advance()
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.advance();
+        let mut text = String::new();
+        loop {
+            match self.advance() {
+                Some('"') => return Ok(text),
+                Some('\\') => text.push(self.escape()?),
+                Some(char) if char < ' ' => {
+                    return Err(self.error("Control characters must be escaped in strings"))
+                }
+                Some(char) => text.push(char),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, String> {
+        let escaped = match self.advance() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => return self.unicode_escape(),
+            _ => return Err(self.error("Invalid escape")),
+        };
+        Ok(escaped)
+    }
+
+    /// `\uXXXX`, where a high surrogate must be followed by an escaped low one.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                if !self.text[self.at..].starts_with("\\u") {
+                    return Err(self.error("Unpaired surrogate in \\u escape"));
+                }
+                self.advance();
+                self.advance();
+                let low = self.hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(self.error("Unpaired surrogate in \\u escape"));
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            }
+            0xDC00..=0xDFFF => return Err(self.error("Unpaired surrogate in \\u escape")),
+            code => code,
+        };
+        char::from_u32(code).ok_or_else(|| self.error("Invalid \\u escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.peek().and_then(|char| char.to_digit(16));
+            let Some(digit) = digit else {
+                return Err(self.error("Expected four hex digits in \\u escape"));
+            };
+            self.advance();
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    /// `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`
+    fn number(&mut self) -> Result<LiteralTypes, String> {
+        let start = self.at;
+        self.advance_if('-');
+        match self.peek() {
+            Some('0') => {
+                self.advance();
+            }
+            Some('1'..='9') => self.digits(),
+            _ => return Err(self.expected("a digit")),
+        }
+        let mut whole = true;
+        if self.advance_if('.').is_some() {
+            whole = false;
+            self.required_digits()?;
+        }
+        if self
+            .advance_if('e')
+            .or_else(|| self.advance_if('E'))
+            .is_some()
+        {
+            whole = false;
+            self.advance_if('+').or_else(|| self.advance_if('-'));
+            self.required_digits()?;
+        }
+
+        let literal = &self.text[start..self.at];
+        if whole {
+            if let Some(int) = BigInt::parse(literal) {
+                return Ok(LiteralTypes::integer(int));
+            }
+        }
+        match literal.parse::<f64>() {
+            Ok(num) if num.is_finite() => Ok(LiteralTypes::Number(num)),
+            _ => Err(self.error(&format!("Number {} is out of range", literal))),
+        }
+    }
+
+    fn digits(&mut self) {
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.advance();
+        }
+    }
+
+    fn required_digits(&mut self) -> Result<(), String> {
+        if !matches!(self.peek(), Some('0'..='9')) {
+            return Err(self.expected("a digit"));
+        }
+        self.digits();
+        Ok(())
+    }
+}
+
+struct Writer {
+    out: String,
+    /// What each level is indented by; `None` writes everything on one line.
+    indent: Option<String>,
+    /// How many arrays and objects enclose what is being written.
+    depth: usize,
+    /// The lists and maps being written, to refuse cycles.
+    seen: Vec<*const ()>,
+}
+
+impl Writer {
+    fn value(&mut self, value: &LiteralTypes) -> Result<(), String> {
+        match value {
+            LiteralTypes::Nil => self.out.push_str("null"),
+            LiteralTypes::Bool(_) | LiteralTypes::Int(_) | LiteralTypes::BigInt(_) => {
+                self.out.push_str(&value.stringify())
+            }
+            LiteralTypes::Decimal(num) => self.out.push_str(&num.to_string()),
+            LiteralTypes::Number(num) if num.is_finite() => self.out.push_str(&value.stringify()),
+            LiteralTypes::Number(num) => {
+                return Err(format!("Can't convert {} to JSON.", num));
+            }
+            LiteralTypes::String(text) => self.string(text),
+            LiteralTypes::List(list) => {
+                self.enter(Rc::as_ptr(list) as *const ())?;
+                let items = list.borrow().clone();
+                self.array(&items)?;
+                self.seen.pop();
+            }
+            LiteralTypes::Tuple(items) => self.array(items)?,
+            LiteralTypes::Map(map) => {
+                self.enter(Rc::as_ptr(map) as *const ())?;
+                let entries = map.borrow().entries().to_vec();
+                self.object(&entries)?;
+                self.seen.pop();
+            }
+            _ => return Err(format!("Can't convert a {} to JSON.", value.type_name())),
+        }
+        Ok(())
+    }
+
+    fn enter(&mut self, container: *const ()) -> Result<(), String> {
+        if self.seen.contains(&container) {
+            return Err("Can't convert a value that contains itself to JSON.".to_string());
+        }
+        self.seen.push(container);
+        Ok(())
+    }
+
+    fn open(&mut self, bracket: char) -> Result<(), String> {
+        if self.depth == MAX_DEPTH {
+            return Err("Value is too deeply nested to convert to JSON.".to_string());
+        }
+        self.depth += 1;
+        self.out.push(bracket);
+        Ok(())
+    }
+
+    fn array(&mut self, items: &[LiteralTypes]) -> Result<(), String> {
+        self.open('[')?;
+        for (i, item) in items.iter().enumerate() {
+            self.separator(i);
+            self.value(item)?;
+        }
+        self.close(items.is_empty(), ']');
+        Ok(())
+    }
+
+    fn object(&mut self, entries: &[(LiteralTypes, LiteralTypes)]) -> Result<(), String> {
+        self.open('{')?;
+        for (i, (key, value)) in entries.iter().enumerate() {
+            self.separator(i);
+            match key {
+                LiteralTypes::String(key) => self.string(key),
+                key if key.as_f64().is_some() => self.string(&key.stringify()),
+                key => {
+                    return Err(format!(
+                        "JSON object keys must be strings or numbers, got {}.",
+                        key.type_name()
+                    ))
+                }
+            }
+            self.out.push(':');
+            if self.indent.is_some() {
+                self.out.push(' ');
+            }
+            self.value(value)?;
+        }
+        self.close(entries.is_empty(), '}');
+        Ok(())
+    }
+
+    /// What goes before the `i`th item of the innermost array or object.
+    fn separator(&mut self, i: usize) {
+        if i > 0 {
+            self.out.push(',');
+        }
+        self.newline(self.depth);
+    }
+
+    fn close(&mut self, empty: bool, bracket: char) {
+        self.depth -= 1;
+        if !empty {
+            self.newline(self.depth);
+        }
+        self.out.push(bracket);
+    }
+
+    fn newline(&mut self, depth: usize) {
+        if let Some(indent) = &self.indent {
+            self.out.push('\n');
+            self.out.push_str(&indent.repeat(depth));
+        }
+    }
+
+    fn string(&mut self, text: &str) {
+        self.out.push('"');
+        for char in text.chars() {
+            match char {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                char if char < ' ' => self.out.push_str(&format!("\\u{:04x}", char as u32)),
+                char => self.out.push(char),
+            }
+        }
+        self.out.push('"');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_errors_have_positions() {
+        for (text, message) in [
+            (
+                "",
+                "Expected a JSON value but the text ended at line 1, column 1.",
+            ),
+            (
+                "[1, 2",
+                "Expected ',' or ']' but the text ended at line 1, column 6.",
+            ),
+            (
+                "{\n  \"a\": tru\n}",
+                "Expected a JSON value but found 't' at line 2, column 8.",
+            ),
+            (
+                "{\"a\" 1}",
+                "Expected ':' but found '1' at line 1, column 6.",
+            ),
+            (
+                "[01]",
+                "Expected ',' or ']' but found '1' at line 1, column 3.",
+            ),
+            (
+                "1.",
+                "Expected a digit but the text ended at line 1, column 3.",
+            ),
+            (
+                "\"a\nb\"",
+                "Control characters must be escaped in strings at line 2, column 1.",
+            ),
+            ("\"\\x\"", "Invalid escape at line 1, column 4."),
+            (
+                "\"\\ud800\"",
+                "Unpaired surrogate in \\u escape at line 1, column 8.",
+            ),
+            (
+                "1 2",
+                "Unexpected data after the JSON value at line 1, column 3.",
+            ),
+            (
+                "[1,]",
+                "Expected a JSON value but found ']' at line 1, column 4.",
+            ),
+            ("1e999", "Number 1e999 is out of range at line 1, column 6."),
+        ] {
+            assert_eq!(parse(text).unwrap_err(), message, "{:?}", text);
+        }
+
+        let deep = "[".repeat(MAX_DEPTH + 1);
+        assert!(parse(&deep).unwrap_err().starts_with("Too deeply nested"));
+    }
+
+    #[test]
+    fn escapes_round_trip() {
+        let text = r#""q\" b\\ s\/ \b\f\n\r\t \u00e9 \ud83d\ude00""#;
+        let value = parse(text).unwrap();
+        assert_eq!(
+            value,
+            LiteralTypes::String("q\" b\\ s/ \u{8}\u{c}\n\r\t é 😀".into())
+        );
+
+        let mut writer = Writer {
+            out: String::new(),
+            indent: None,
+            depth: 0,
+            seen: Vec::new(),
+        };
+        writer.value(&value).unwrap();
+        assert_eq!(writer.out, r#""q\" b\\ s/ \u0008\u000c\n\r\t é 😀""#);
+        assert_eq!(parse(&writer.out).unwrap(), value);
+    }
+}
@@ -0,0 +1,150 @@
+//! Built-in methods of list values: `xs.push(1)`, `xs.len()`, ...
+
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+use crate::lexer::LiteralTypes;
+
+use super::{callable::NativeError, exception::ErrorKind};
+
+type List = Rc<RefCell<Vec<LiteralTypes>>>;
+
+/// Call the method `name` on `list`. Bad indexes are index errors, like those of
+/// `xs[i]`; the interpreter attributes errors to the call site.
+pub fn call_method(
+    list: &List,
+    name: &str,
+    args: Vec<LiteralTypes>,
+) -> Result<LiteralTypes, NativeError> {
+    let arity = match name {
+        "pop" | "len" | "sort" | "reverse" => 0,
+        "push" | "remove" | "contains" | "join" => 1,
+        "insert" => 2,
+        _ => return Err(format!("Undefined method '{}' on list.", name).into()),
+    };
+    if args.len() != arity {
+        return Err(format!("Expected {} arguments but got {}.", arity, args.len()).into());
+    }
+    let index_error = |message| NativeError::Error(ErrorKind::Index, message);
+
+    let mut args = args.into_iter();
+    let mut arg = || args.next().unwrap_or(LiteralTypes::Nil);
+    match name {
+        "push" => {
+            list.borrow_mut().push(arg());
+            Ok(LiteralTypes::Nil)
+        }
+        "pop" => list
+            .borrow_mut()
+            .pop()
+            .ok_or_else(|| "Can't pop from an empty list.".to_string().into()),
+        "len" => Ok(LiteralTypes::Int(list.borrow().len() as i64)),
+        "insert" => {
+            let len = list.borrow().len();
+            let index = arg();
+            // `len` itself appends; past that, the error should name the real length
+            let at = position(&index, len + 1, "list")
+                .or_else(|_| position(&index, len, "list"))
+                .map_err(index_error)?;
+            list.borrow_mut().insert(at, arg());
+            Ok(LiteralTypes::Nil)
+        }
+        "remove" => {
+            let len = list.borrow().len();
+            let at = position(&arg(), len, "list").map_err(index_error)?;
+            Ok(list.borrow_mut().remove(at))
+        }
+        "sort" => Ok(sort(list)?),
+        "reverse" => {
+            list.borrow_mut().reverse();
+            Ok(LiteralTypes::Nil)
+        }
+        "contains" => {
+            let needle = arg();
+            let found = list.borrow().iter().any(|item| item.equal(&needle));
+            Ok(LiteralTypes::Bool(found))
+        }
+        "join" => match arg() {
+            LiteralTypes::String(separator) => {
+                let items: Vec<String> = list.borrow().iter().map(|item| item.to_text()).collect();
+                Ok(LiteralTypes::String(items.join(&separator)))
+            }
+            other => Err(format!("Separator must be a string, got {}.", other.type_name()).into()),
+        },
+        _ => unreachable!(),
+    }
+}
+
+/// Resolve a possibly negative `index` into `0..len`, for errors about the `len`
+/// items of a value named `of`.
+pub fn position(index: &LiteralTypes, len: usize, of: &str) -> Result<usize, String> {
+    let LiteralTypes::Int(index) = index else {
+        return Err(format!(
+            "Index into a {} must be an int, got {}.",
+            of,
+            index.type_name()
+        ));
+    };
+    let resolved = if *index < 0 {
+        *index + len as i64
+    } else {
+        *index
+    };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!(
+            "Index {} out of range for {} of length {}.",
+            index, of, len
+        ));
+    }
+
+    Ok(resolved as usize)
+}
+
+/// Resolve slice bounds the way Python does: negative bounds count from the end and
+/// out-of-range bounds are clamped.
+pub fn slice_bounds(
+    start: Option<&LiteralTypes>,
+    end: Option<&LiteralTypes>,
+    len: usize,
+) -> Result<(usize, usize), String> {
+    let bound = |value: Option<&LiteralTypes>, default: usize| match value {
+        None | Some(LiteralTypes::Nil) => Ok(default),
+        Some(LiteralTypes::Int(index)) => {
+            let resolved = if *index < 0 {
+                *index + len as i64
+            } else {
+                *index
+            };
+            Ok(resolved.clamp(0, len as i64) as usize)
+        }
+        Some(other) => Err(format!(
+            "Slice bound must be an int, got {}.",
+            other.type_name()
+        )),
+    };
+    let start = bound(start, 0)?;
+    let end = bound(end, len)?;
+
+    Ok((start, end.max(start)))
+}
+
+/// Sort numbers or strings in place; other or mixed element types are an error.
+fn sort(list: &List) -> Result<LiteralTypes, String> {
+    let mut error = None;
+    list.borrow_mut().sort_by(|a, b| {
+        let ordering = match (a, b) {
+            (LiteralTypes::String(a), LiteralTypes::String(b)) => Some(a.cmp(b)),
+            _ => a.compare(b),
+        };
+        ordering.unwrap_or_else(|| {
+            error.get_or_insert_with(|| {
+                format!("Can't compare {} with {}.", a.type_name(), b.type_name())
+            });
+            Ordering::Equal
+        })
+    });
+
+    match error {
+        Some(error) => Err(error),
+        None => Ok(LiteralTypes::Nil),
+    }
+}
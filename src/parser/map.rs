@@ -0,0 +1,138 @@
+//! The map value and its built-in methods: `m.keys()`, `m.has("a")`, ...
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{lexer::LiteralTypes, number::BigInt};
+
+/// An insertion-ordered hash map keyed by strings and numbers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Map {
+    entries: Vec<(LiteralTypes, LiteralTypes)>,
+    index: HashMap<MapKey, usize>,
+}
+
+/// The hashable form of a key. Keys that are `equal` share a `MapKey`: every numeric
+/// type with a whole value (`1`, `1.0`, `1d`, `-0.0`) becomes an integer, and other
+/// floats and decimals are keyed by their `f64` bits, which is how `equal` compares
+/// them. `NaN` is rejected since it is never equal to itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MapKey {
+    String(String),
+    Int(i64),
+    BigInt(BigInt),
+    Float(u64),
+}
+
+impl MapKey {
+    fn new(key: &LiteralTypes) -> Result<Self, String> {
+        let integer = match key {
+            LiteralTypes::String(str) => return Ok(MapKey::String(str.clone())),
+            LiteralTypes::Number(num) if num.is_nan() => {
+                return Err("NaN can't be used as a map key.".to_string())
+            }
+            LiteralTypes::Number(num) => BigInt::from_f64(*num).ok_or(*num),
+            LiteralTypes::Int(_) | LiteralTypes::BigInt(_) => Ok(key.to_bigint().unwrap()),
+            LiteralTypes::Decimal(num) => num.to_integer().ok_or_else(|| num.to_f64()),
+            _ => {
+                return Err(format!(
+                    "Map keys must be strings or numbers, got {}.",
+                    key.type_name()
+                ))
+            }
+        };
+
+        Ok(match integer.map(LiteralTypes::integer) {
+            Ok(LiteralTypes::Int(num)) => MapKey::Int(num),
+            Ok(LiteralTypes::BigInt(num)) => MapKey::BigInt(num),
+            Ok(_) => unreachable!(),
+            Err(float) => MapKey::Float(float.to_bits()),
+        })
+    }
+}
+
+impl Map {
+    pub fn get(&self, key: &LiteralTypes) -> Result<Option<LiteralTypes>, String> {
+        let key = MapKey::new(key)?;
+        Ok(self.index.get(&key).map(|at| self.entries[*at].1.clone()))
+    }
+
+    /// Insert or overwrite; an existing key keeps its place in the iteration order.
+    pub fn insert(&mut self, key: LiteralTypes, value: LiteralTypes) -> Result<(), String> {
+        let hashed = MapKey::new(&key)?;
+        match self.index.get(&hashed) {
+            Some(at) => self.entries[*at].1 = value,
+            None => {
+                self.index.insert(hashed, self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &LiteralTypes) -> Result<Option<LiteralTypes>, String> {
+        let Some(at) = self.index.remove(&MapKey::new(key)?) else {
+            return Ok(None);
+        };
+        let (_, value) = self.entries.remove(at);
+        for position in self.index.values_mut() {
+            if *position > at {
+                *position -= 1;
+            }
+        }
+        Ok(Some(value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn entries(&self) -> &[(LiteralTypes, LiteralTypes)] {
+        &self.entries
+    }
+}
+
+/// Call the method `name` on `map`. Errors are plain messages; the interpreter
+/// attributes them to the call site.
+pub fn call_method(
+    map: &Rc<RefCell<Map>>,
+    name: &str,
+    args: Vec<LiteralTypes>,
+) -> Result<LiteralTypes, String> {
+    let arity = match name {
+        "keys" | "values" | "len" => 0,
+        "has" | "delete" => 1,
+        _ => return Err(format!("Undefined method '{}' on map.", name)),
+    };
+    if args.len() != arity {
+        return Err(format!(
+            "Expected {} arguments but got {}.",
+            arity,
+            args.len()
+        ));
+    }
+
+    let list = |items: Vec<LiteralTypes>| LiteralTypes::List(Rc::new(RefCell::new(items)));
+    match name {
+        "keys" => Ok(list(
+            map.borrow()
+                .entries()
+                .iter()
+                .map(|(k, _)| k.clone())
+                .collect(),
+        )),
+        "values" => Ok(list(
+            map.borrow()
+                .entries()
+                .iter()
+                .map(|(_, v)| v.clone())
+                .collect(),
+        )),
+        "len" => Ok(LiteralTypes::Int(map.borrow().len() as i64)),
+        "has" => Ok(LiteralTypes::Bool(map.borrow().get(&args[0])?.is_some())),
+        "delete" => Ok(map
+            .borrow_mut()
+            .remove(&args[0])?
+            .unwrap_or(LiteralTypes::Nil)),
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,305 @@
+//! The `math` module: floating-point functions, constants and a seedable random generator.
+//!
+//! Functions take any number and, apart from `abs`, `min`, `max` and the rounding ones,
+//! return a float. An argument outside a function's domain is an `ArithmeticError`
+//! instead of a `NaN`.
+
+use std::{
+    cell::Cell,
+    f64::consts,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{lexer::LiteralTypes, number::BigInt};
+
+use super::{
+    callable::{Callable, NativeError},
+    exception::ErrorKind,
+};
+
+pub fn exports() -> Vec<(&'static str, LiteralTypes)> {
+    let random = Rc::new(Random::from_clock());
+    let seeded = random.clone();
+    let ranged = random.clone();
+
+    vec![
+        ("pi", LiteralTypes::Number(consts::PI)),
+        ("e", LiteralTypes::Number(consts::E)),
+        unary("sqrt", |x| {
+            if x < 0.0 {
+                return Err(domain_error("sqrt", x));
+            }
+            Ok(x.sqrt())
+        }),
+        unary("sin", |x| Ok(x.sin())),
+        unary("cos", |x| Ok(x.cos())),
+        unary("tan", |x| Ok(x.tan())),
+        unary("exp", |x| Ok(x.exp())),
+        native("log", 1..=2, |args| {
+            let x = float("log", &args[0])?;
+            let base = args.get(1).map(|base| float("log", base)).transpose()?;
+            if x <= 0.0 {
+                return Err(domain_error("log", x));
+            }
+            match base {
+                Some(base) if base <= 0.0 || base == 1.0 => Err(NativeError::Error(
+                    ErrorKind::Arithmetic,
+                    format!("log base must be positive and not 1, got {}.", base),
+                )),
+                Some(base) => finite("log", x.log(base)),
+                None => finite("log", x.ln()),
+            }
+        }),
+        native("pow", 2..=2, |args| {
+            let (x, y) = (float("pow", &args[0])?, float("pow", &args[1])?);
+            if x < 0.0 && y.fract() != 0.0 {
+                return Err(NativeError::Error(
+                    ErrorKind::Arithmetic,
+                    format!("pow of negative {} to the fractional power {}.", x, y),
+                ));
+            }
+            if x == 0.0 && y < 0.0 {
+                return Err(NativeError::Error(
+                    ErrorKind::Arithmetic,
+                    "pow of zero to a negative power.".to_string(),
+                ));
+            }
+            finite("pow", x.powf(y))
+        }),
+        native("atan2", 2..=2, |args| {
+            let (y, x) = (float("atan2", &args[0])?, float("atan2", &args[1])?);
+            finite("atan2", y.atan2(x))
+        }),
+        native("abs", 1..=1, |args| match &args[0] {
+            LiteralTypes::Int(num) => Ok(num
+                .checked_abs()
+                .map(LiteralTypes::Int)
+                .unwrap_or_else(|| LiteralTypes::BigInt(BigInt::from(*num).abs()))),
+            LiteralTypes::BigInt(num) => Ok(LiteralTypes::integer(num.abs())),
+            other => Ok(LiteralTypes::Number(float("abs", other)?.abs())),
+        }),
+        rounding("floor", f64::floor),
+        rounding("ceil", f64::ceil),
+        rounding("round", f64::round),
+        native("min", 1..=usize::MAX, |args| {
+            extreme("min", args, |o| o.is_lt())
+        }),
+        native("max", 1..=usize::MAX, |args| {
+            extreme("max", args, |o| o.is_gt())
+        }),
+        (
+            "random",
+            Callable::native("random", 0..=0, move |_, _| {
+                Ok(LiteralTypes::Number(random.next_f64()))
+            }),
+        ),
+        (
+            "seed",
+            Callable::native("seed", 1..=1, move |_, args| match &args[0] {
+                LiteralTypes::Int(seed) => {
+                    seeded.seed(*seed as u64);
+                    Ok(LiteralTypes::Nil)
+                }
+                LiteralTypes::BigInt(seed) => Err(NativeError::Error(
+                    ErrorKind::Arithmetic,
+                    format!("seed must fit in 64 bits, got {}.", seed),
+                )),
+                other => Err(NativeError::type_error(format!(
+                    "seed expects an int, got {}.",
+                    other.type_name()
+                ))),
+            }),
+        ),
+        (
+            "random_int",
+            Callable::native("random_int", 2..=2, move |_, args| {
+                match (&args[0], &args[1]) {
+                    (LiteralTypes::Int(low), LiteralTypes::Int(high)) if low <= high => {
+                        let span = high.abs_diff(*low).wrapping_add(1);
+                        let offset = match span {
+                            0 => ranged.next_u64(),
+                            span => ranged.below(span),
+                        };
+                        Ok(LiteralTypes::Int(low.wrapping_add(offset as i64)))
+                    }
+                    (LiteralTypes::Int(low), LiteralTypes::Int(high)) => Err(NativeError::Error(
+                        ErrorKind::Arithmetic,
+                        format!("random_int range {}..={} is empty.", low, high),
+                    )),
+                    (
+                        LiteralTypes::Int(_) | LiteralTypes::BigInt(_),
+                        LiteralTypes::Int(_) | LiteralTypes::BigInt(_),
+                    ) => Err(NativeError::Error(
+                        ErrorKind::Arithmetic,
+                        "random_int bounds must fit in 64 bits.".to_string(),
+                    )),
+                    _ => Err(NativeError::type_error("random_int expects two ints.")),
+                }
+            }),
+        ),
+    ]
+}
+
+/// A SplitMix64 generator: tiny, fast, and the same sequence for the same seed on
+/// every platform.
+#[derive(Debug)]
+pub struct Random {
+    state: Cell<u64>,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Cell::new(seed),
+        }
+    }
+
+    fn from_clock() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self::new(now.as_nanos() as u64)
+    }
+
+    pub fn seed(&self, seed: u64) {
+        self.state.set(seed);
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`, from the top 53 bits.
+    pub fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `0..bound`, rejecting the values that would bias the remainder.
+    fn below(&self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+fn native(
+    name: &'static str,
+    arity: std::ops::RangeInclusive<usize>,
+    function: impl Fn(Vec<LiteralTypes>) -> Result<LiteralTypes, NativeError> + 'static,
+) -> (&'static str, LiteralTypes) {
+    (
+        name,
+        Callable::native(name, arity, move |_, args| function(args)),
+    )
+}
+
+fn unary(
+    name: &'static str,
+    function: impl Fn(f64) -> Result<f64, NativeError> + 'static,
+) -> (&'static str, LiteralTypes) {
+    native(name, 1..=1, move |args| {
+        finite(name, function(float(name, &args[0])?)?)
+    })
+}
+
+/// `floor`, `ceil` and `round`: ints stay as they are, floats become ints.
+fn rounding(name: &'static str, function: fn(f64) -> f64) -> (&'static str, LiteralTypes) {
+    native(name, 1..=1, move |args| match &args[0] {
+        LiteralTypes::Int(_) | LiteralTypes::BigInt(_) => Ok(args[0].clone()),
+        other => {
+            let x = float(name, other)?;
+            BigInt::from_f64(function(x))
+                .map(LiteralTypes::integer)
+                .ok_or_else(|| domain_error(name, x))
+        }
+    })
+}
+
+/// The argument `keep` prefers over every other, of the arguments or of a single list.
+fn extreme(
+    name: &str,
+    args: Vec<LiteralTypes>,
+    keep: fn(std::cmp::Ordering) -> bool,
+) -> Result<LiteralTypes, NativeError> {
+    let values = match args.as_slice() {
+        [LiteralTypes::List(list)] => list.borrow().clone(),
+        _ => args,
+    };
+    let mut values = values.into_iter();
+    let mut best = values.next().ok_or_else(|| {
+        NativeError::Error(ErrorKind::Arithmetic, format!("{} of an empty list.", name))
+    })?;
+    float(name, &best)?;
+    for value in values {
+        match value.compare(&best) {
+            Some(ordering) if keep(ordering) => best = value,
+            Some(_) => {}
+            None => {
+                float(name, &value)?;
+                return Err(NativeError::Error(
+                    ErrorKind::Arithmetic,
+                    format!("{} can't order NaN.", name),
+                ));
+            }
+        }
+    }
+    Ok(best)
+}
+
+fn float(name: &str, value: &LiteralTypes) -> Result<f64, NativeError> {
+    value.as_f64().ok_or_else(|| {
+        NativeError::type_error(format!(
+            "{} expects a number, got {}.",
+            name,
+            value.type_name()
+        ))
+    })
+}
+
+/// A result that left the finite floats came from a domain error or an overflow.
+fn finite(name: &str, result: f64) -> Result<LiteralTypes, NativeError> {
+    if !result.is_finite() {
+        return Err(NativeError::Error(
+            ErrorKind::Arithmetic,
+            format!("{} result is out of range.", name),
+        ));
+    }
+    Ok(LiteralTypes::Number(result))
+}
+
+fn domain_error(name: &str, x: f64) -> NativeError {
+    NativeError::Error(
+        ErrorKind::Arithmetic,
+        format!("{} is undefined for {}.", name, x),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_is_deterministic() {
+        let (a, b) = (Random::new(42), Random::new(42));
+        let first: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..5).map(|_| b.next_u64()).collect::<Vec<_>>());
+
+        a.seed(42);
+        assert_eq!(a.next_u64(), first[0]);
+        for _ in 0..1000 {
+            let x = a.next_f64();
+            assert!((0.0..1.0).contains(&x));
+            assert!(a.below(6) < 6);
+        }
+    }
+}
@@ -1,24 +1,84 @@
 //！```dnf
-//！expression     → equality ;
+//！declaration    → ( "var" | "const" ) target ( "=" expression )? ";" | enum | import | statement ;
+//！import         → "import" STRING "as" IDENTIFIER ";"
+//！               | "from" STRING "import" IDENTIFIER ( "," IDENTIFIER )* ";" ;
+//！statement      → ... | "throw" expression ";" | try_stmt ;
+//！try_stmt       → "try" block ( "catch" ( "(" IDENTIFIER ")" )? block )? ( "finally" block )? ;
+//！enum           → "enum" IDENTIFIER "{" ( variant ( "," variant )* ","? )? "}" ;
+//！variant        → IDENTIFIER ( "(" IDENTIFIER ( "," IDENTIFIER )* ")" )? ;
+//！expression     → assignment ;
+//！assignment     → ( IDENTIFIER | call "[" expression "]" )
+//！                 ( "=" | "+=" | "-=" | "*=" | "/=" | "%=" ) assignment
+//！               | sequence "=" assignment
+//！               | conditional ;
+//！conditional    → or ( "?" expression ":" conditional )? ;
+//！or             → and ( "or" and )* ;
+//！and            → bit_or ( "and" bit_or )* ;
+//！bit_or         → bit_xor ( "|" bit_xor )* ;
+//！bit_xor        → bit_and ( "^" bit_and )* ;
+//！bit_and        → equality ( "&" equality )* ;
 //！equality       → comparison ( ( "!=" | "==" ) comparison )* ;
-//！comparison     → term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
+//！comparison     → range ( ( ">" | ">=" | "<" | "<=" ) range )* ;
+//！range          → shift ( ( ".." | "..=" ) shift ( "step" shift )? )? ;
+//！shift          → term ( ( "<<" | ">>" ) term )* ;
 //！term           → factor ( ( "-" | "+" ) factor )* ;
-//！factor         → unary ( ( "/" | "*" ) unary )* ;
-//！unary          → ( "!" | "-" ) unary
-//！               | primary ;
+//！factor         → unary ( ( "/" | "*" | "%" | "~/" ) unary )* ;
+//！unary          → ( "!" | "-" | "~" | "++" | "--" ) unary
+//！               | power ;
+//！power          → postfix ( "**" unary )? ;
+//！postfix        → call ( "++" | "--" )? ;
+//！call           → primary ( "(" arguments? ")" | "[" subscript "]" | "." IDENTIFIER )* ;
+//！subscript      → expression | expression? ":" expression? ;
 //！primary        → NUMBER | STRING | "true" | "false" | "nil"
-//！               | "(" expression ")" ;
+//！               | "(" expression ")" | tuple | "[" arguments? ","? "]" | map | match ;
+//！tuple          → "(" ( expression "," arguments? )? ")" ;
+//！map            → "{" ( entry ( "," entry )* ","? )? "}" ;
+//！entry          → expression ":" expression ;
+//！match          → "match" "(" expression ")" "{" ( arm ( "," arm )* ","? )? "}" ;
+//！arm            → pattern ( "|" pattern )* ( "if" expression )? "=>" expression ;
+//！pattern        → "_" | literal ( ( ".." | "..=" ) literal )? ;
+//！target         → IDENTIFIER | sequence ;
+//！sequence       → ( "(" | "[" ) ( ( target | "..." IDENTIFIER ) ( "," ... )* ","? )? ( ")" | "]" ) ;
 //! ```
 
-use ast::{Assign, Binary, Comparison, Expr, Logical, Variable};
+use ast::{Assign, Binary, Comparison, Expr, Logical, MatchArm, Pattern, Target, Variable};
 use stmt::{Block, Expression, If, Print, Stmt, While};
 
+use std::{collections::HashMap, io::Write, path::Path};
+
 use crate::lexer::{Lexer, LiteralTypes, Token, TokenType};
 use ast::Visitor;
+pub(crate) use callable::Callable;
+pub(crate) use enums::{EnumType, Variant};
+pub(crate) use exception::ErrorObject;
+pub use fs::Access;
+pub use inter::Interpreter;
+pub(crate) use iter::Range;
+pub(crate) use map::Map;
+pub(crate) use module::Module;
+pub use time::{Clock, FakeClock, SystemClock};
+
 mod ast;
+mod callable;
+mod enums;
 mod env;
+mod exception;
+mod format;
+mod fs;
 mod inter;
+mod iter;
+mod json;
+mod list;
+mod map;
+mod math;
+mod module;
+mod natives;
+mod output;
+mod process;
+mod re;
 mod stmt;
+mod string;
+mod time;
 
 macro_rules! error_message {
     ($literal:literal) => {
@@ -29,15 +89,79 @@ macro_rules! error_message {
     };
 }
 
+/// Exit status for a script that doesn't parse.
+const EXIT_DATA_ERROR: i32 = 65;
+/// Exit status for a script that can't be read.
+const EXIT_NO_INPUT: i32 = 66;
+/// Exit status for a runtime error nothing caught.
+const EXIT_SOFTWARE: i32 = 70;
+
+/// Parse and execute `source`, reporting the first error to stderr. Returns the exit
+/// status: what the script passed to `exit()`, or 0 when it ran to the end.
+pub fn run(source: &str) -> i32 {
+    run_in(&mut Interpreter::new(), source)
+}
+
+/// Like [`run`], but in an interpreter the embedder has set up, e.g. with its own
+/// output sinks. Errors are reported to the interpreter's stderr.
+pub fn run_in(interpreter: &mut Interpreter, source: &str) -> i32 {
+    let result = match Parser::new(source).parse() {
+        Ok(statements) => interpreter.interpret(&statements),
+        Err(Error::String(message)) => {
+            let _ = writeln!(interpreter.output().stderr, "{}", message);
+            return EXIT_DATA_ERROR;
+        }
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(inter::RuntimeError::Exit(code)) => code,
+        Err(error) => {
+            let _ = writeln!(interpreter.output().stderr, "{}", error);
+            EXIT_SOFTWARE
+        }
+    }
+}
+
+/// Run the script at `path` with `args()` returning `args`, and return its exit status
+/// like [`run`]. Imports resolve relative to it, then through the directories listed
+/// in the `LOX_PATH` environment variable.
+pub fn run_file(path: &Path, args: Vec<String>) -> i32 {
+    let search_path = std::env::var_os("LOX_PATH")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    let mut interpreter = Interpreter::new()
+        .with_script(path)
+        .with_search_path(search_path)
+        .with_args(args);
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => {
+            let stderr = &mut interpreter.output().stderr;
+            let _ = writeln!(stderr, "Can't read {}: {}", path.display(), err);
+            return EXIT_NO_INPUT;
+        }
+    };
+    run_in(&mut interpreter, &source)
+}
+
 struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    /// The names declared in each enclosing scope, and whether they are `const`. Used
+    /// to reject writes to constants before anything runs; names not found here (such
+    /// as globals defined elsewhere) are left to the checks in `Environment`.
+    scopes: Vec<HashMap<String, bool>>,
 }
 
 impl Parser {
     pub fn new(source: &str) -> Self {
         let tokens = Lexer::new(source).scan_tokens();
-        Self { tokens, current: 0 }
+        Self {
+            tokens,
+            current: 0,
+            scopes: vec![HashMap::new()],
+        }
     }
 
     pub fn parse(&mut self) -> Result<Vec<Stmt>, Error> {
@@ -50,25 +174,231 @@ impl Parser {
     }
 
     fn declaration(&mut self) -> Result<Stmt, Error> {
-        if self.is_match(&[TokenType::Var]) {
+        if self.is_match(&[TokenType::Var, TokenType::Const]) {
             return self.var_declaration();
         }
+        if self.is_match(&[TokenType::Enum]) {
+            return self.enum_declaration();
+        }
+        if self.is_match(&[TokenType::Import]) {
+            return self.import_declaration();
+        }
+        // `from` is only special in front of a module path, so it stays usable as a name
+        if self.check(&TokenType::Identifier)
+            && self.peek().lexeme == "from"
+            && self.peek_next().ttype == TokenType::String
+        {
+            self.advance();
+            return self.selective_import();
+        }
         self.statement()
     }
-    fn var_declaration(&mut self) -> Result<Stmt, Error> {
-        self.consume(&TokenType::Identifier, "Expect variable name.")?;
+
+    /// import         → "import" STRING "as" IDENTIFIER ";" ;
+    fn import_declaration(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
+        self.consume(&TokenType::String, "Expect module path after 'import'.")?;
+        let path = self.previous();
+        if !(self.check(&TokenType::Identifier) && self.peek().lexeme == "as") {
+            return Err(self.error("Expect 'as' after module path."));
+        }
+        self.advance();
+        self.consume(&TokenType::Identifier, "Expect module name after 'as'.")?;
         let name = self.previous();
+        self.consume(&TokenType::Semicolon, "Expect ';' after import.")?;
+        self.declare(&Target::Name(name.clone()), false)?;
+
+        Ok(Stmt::Import(stmt::Import {
+            keyword,
+            path,
+            names: stmt::ImportNames::Module(name),
+        }))
+    }
+
+    /// import         → "from" STRING "import" IDENTIFIER ( "," IDENTIFIER )* ";" ;
+    fn selective_import(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
+        self.consume(&TokenType::String, "Expect module path after 'from'.")?;
+        let path = self.previous();
+        self.consume(&TokenType::Import, "Expect 'import' after module path.")?;
+
+        let mut names = Vec::new();
+        loop {
+            self.consume(&TokenType::Identifier, "Expect name to import.")?;
+            let name = self.previous();
+            self.declare(&Target::Name(name.clone()), false)?;
+            names.push(name);
+            if !self.is_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&TokenType::Semicolon, "Expect ';' after import.")?;
+
+        Ok(Stmt::Import(stmt::Import {
+            keyword,
+            path,
+            names: stmt::ImportNames::Exports(names),
+        }))
+    }
+
+    fn enum_declaration(&mut self) -> Result<Stmt, Error> {
+        self.consume(&TokenType::Identifier, "Expect enum name.")?;
+        let name = self.previous();
+        self.consume(&TokenType::LeftBrace, "Expect '{' before enum variants.")?;
+
+        let mut variants: Vec<(Token, Vec<Token>)> = Vec::new();
+        while !self.check(&TokenType::RightBrace) {
+            self.consume(&TokenType::Identifier, "Expect variant name.")?;
+            let variant = self.previous();
+            if variants.iter().any(|(v, _)| v.lexeme == variant.lexeme) {
+                let message = format!("Duplicate variant '{}'.", variant.lexeme);
+                return Err(self.token_error(&variant, &message));
+            }
+
+            let mut fields: Vec<Token> = Vec::new();
+            if self.is_match(&[TokenType::LeftParen]) {
+                loop {
+                    self.consume(&TokenType::Identifier, "Expect payload field name.")?;
+                    let field = self.previous();
+                    // `.tag` is how a variant's name is read
+                    if field.lexeme == "tag" || fields.iter().any(|f| f.lexeme == field.lexeme) {
+                        let message = format!("Invalid payload field '{}'.", field.lexeme);
+                        return Err(self.token_error(&field, &message));
+                    }
+                    fields.push(field);
+                    if !self.is_match(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+                self.consume(&TokenType::RightParen, "Expect ')' after payload fields.")?;
+            }
+            variants.push((variant, fields));
+
+            if !self.is_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&TokenType::RightBrace, "Expect '}' after enum variants.")?;
+        // the enum itself can't be rebound, like a constant
+        self.declare(&Target::Name(name.clone()), true)?;
+
+        Ok(Stmt::Enum(stmt::Enum { name, variants }))
+    }
+    fn var_declaration(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
+        let constant = keyword.ttype == TokenType::Const;
+        let target = self.target()?;
 
         let mut initializer = Expr::Literal(LiteralTypes::Nil);
         if self.is_match(&[TokenType::Equal]) {
             initializer = self.expression()?;
+        } else if let Target::Sequence { open, .. } = &target {
+            return Err(self.token_error(open, "Expect '=' after destructuring pattern."));
+        } else if constant {
+            return Err(self.token_error(&keyword, "Constants must be initialized."));
         }
+        self.declare(&target, constant)?;
 
         self.consume(
             &TokenType::Semicolon,
             "Expect ';' after variable declaration.",
         )?;
-        Ok(Stmt::Var(stmt::Var { name, initializer }))
+        Ok(Stmt::Var(stmt::Var {
+            target,
+            constant,
+            initializer,
+        }))
+    }
+
+    /// Record the names bound by `target` in the innermost scope.
+    fn declare(&mut self, target: &Target, constant: bool) -> Result<(), Error> {
+        match target {
+            Target::Name(name) => {
+                let scope = self.scopes.last_mut().unwrap();
+                if scope.get(&name.lexeme) == Some(&true) {
+                    let message = format!("Can't redeclare constant '{}'.", name.lexeme);
+                    return Err(self.token_error(name, &message));
+                }
+                scope.insert(name.lexeme.clone(), constant);
+            }
+            Target::Sequence { items, rest, .. } => {
+                for item in items {
+                    self.declare(item, constant)?;
+                }
+                if let Some(rest) = rest {
+                    self.declare(&Target::Name(rest.clone()), constant)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject an assignment to any name in `target` that is known to be a constant.
+    fn check_assignable(&self, target: &Target) -> Result<(), Error> {
+        match target {
+            Target::Name(name) => {
+                let constant = self
+                    .scopes
+                    .iter()
+                    .rev()
+                    .find_map(|scope| scope.get(&name.lexeme));
+                if constant == Some(&true) {
+                    let message = format!("Can't assign to constant '{}'.", name.lexeme);
+                    return Err(self.token_error(name, &message));
+                }
+            }
+            Target::Sequence { items, rest, .. } => {
+                for item in items {
+                    self.check_assignable(item)?;
+                }
+                if let Some(rest) = rest {
+                    self.check_assignable(&Target::Name(rest.clone()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse with `parse` inside a new scope for declarations.
+    fn scoped<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, Error>) -> Result<T, Error> {
+        self.scopes.push(HashMap::new());
+        let result = parse(self);
+        self.scopes.pop();
+        result
+    }
+
+    /// target         → IDENTIFIER | sequence ;
+    /// sequence       → ( "(" | "[" ) ( ( target | "..." IDENTIFIER ) ( "," ... )* ","? )? ( ")" | "]" ) ;
+    fn target(&mut self) -> Result<Target, Error> {
+        if self.is_match(&[TokenType::Identifier]) {
+            return Ok(Target::Name(self.previous()));
+        }
+        if !self.is_match(&[TokenType::LeftParen, TokenType::LeftBracket]) {
+            return Err(self.error("Expect variable name."));
+        }
+
+        let open = self.previous();
+        let close = match open.ttype {
+            TokenType::LeftParen => TokenType::RightParen,
+            _ => TokenType::RightBracket,
+        };
+        let mut items = Vec::new();
+        let mut rest = None;
+        while !self.check(&close) {
+            if self.is_match(&[TokenType::DotDotDot]) {
+                self.consume(&TokenType::Identifier, "Expect name after '...'.")?;
+                rest = Some(self.previous());
+                self.is_match(&[TokenType::Comma]);
+                break;
+            }
+            items.push(self.target()?);
+            if !self.is_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&close, "Expect end of destructuring pattern.")?;
+
+        Ok(Target::Sequence { open, items, rest })
     }
 
     fn statement(&mut self) -> Result<Stmt, Error> {
@@ -76,10 +406,10 @@ impl Parser {
             return self.print_statement();
         }
         if self.is_match(&[TokenType::LeftBrace]) {
-            return Ok(Stmt::Block(self.block()?));
+            return Ok(Stmt::Block(self.scoped(Self::block)?));
         }
         if self.is_match(&[TokenType::For]) {
-            return self.for_statement();
+            return self.scoped(Self::for_statement);
         }
         if self.is_match(&[TokenType::If]) {
             return self.if_statement();
@@ -88,14 +418,204 @@ impl Parser {
         if self.is_match(&[TokenType::While]) {
             return self.while_statement();
         }
+        if self.is_match(&[TokenType::Match]) {
+            return self.match_statement();
+        }
+        if self.is_match(&[TokenType::Throw]) {
+            return self.throw_statement();
+        }
+        if self.is_match(&[TokenType::Try]) {
+            return self.try_statement();
+        }
 
         self.expression_statement()
     }
 
+    fn throw_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
+        let value = self.expression()?;
+        self.consume(&TokenType::Semicolon, "Expect ';' after thrown value.")?;
+
+        Ok(Stmt::Throw(stmt::Throw { keyword, value }))
+    }
+
+    /// try_stmt       → "try" block ( "catch" ( "(" IDENTIFIER ")" )? block )? ( "finally" block )? ;
+    fn try_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
+        let body = self.handler_block("try")?;
+
+        let mut catch = None;
+        if self.is_match(&[TokenType::Catch]) {
+            catch = Some(self.scoped(|parser| {
+                let mut name = None;
+                if parser.is_match(&[TokenType::LeftParen]) {
+                    parser.consume(&TokenType::Identifier, "Expect error variable name.")?;
+                    let token = parser.previous();
+                    parser.declare(&Target::Name(token.clone()), false)?;
+                    name = Some(token);
+                    parser.consume(&TokenType::RightParen, "Expect ')' after error variable.")?;
+                }
+                Ok((name, parser.handler_block("catch")?))
+            })?);
+        }
+
+        let mut finally = None;
+        if self.is_match(&[TokenType::Finally]) {
+            finally = Some(self.handler_block("finally")?);
+        }
+        if catch.is_none() && finally.is_none() {
+            return Err(self.token_error(&keyword, "Expect 'catch' or 'finally' after try block."));
+        }
+
+        Ok(Stmt::Try(stmt::Try {
+            body,
+            catch,
+            finally,
+        }))
+    }
+
+    /// The `{ ... }` after `try`, `catch` or `finally`.
+    fn handler_block(&mut self, after: &str) -> Result<Vec<Stmt>, Error> {
+        self.consume(
+            &TokenType::LeftBrace,
+            &format!("Expect '{{' after '{}'.", after),
+        )?;
+        Ok(self.scoped(Self::block)?.statements)
+    }
+
+    /// match_stmt     → "match" "(" expression ")" "{" ( pattern_list "=>" statement )* "}" ;
+    fn match_statement(&mut self) -> Result<Stmt, Error> {
+        let (keyword, subject) = self.match_subject()?;
+
+        let mut arms = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            let (patterns, guard) = self.match_arm_head()?;
+            let body = self.statement()?;
+            arms.push(MatchArm {
+                patterns,
+                guard,
+                body,
+            });
+        }
+        self.consume(&TokenType::RightBrace, "Expect '}' after match arms.")?;
+
+        Ok(Stmt::Match(stmt::Match {
+            keyword,
+            subject,
+            arms,
+        }))
+    }
+
+    /// match_expr     → "match" "(" expression ")" "{" ( arm ( "," arm )* ","? )? "}" ;
+    fn match_expression(&mut self) -> Result<Expr, Error> {
+        let (keyword, subject) = self.match_subject()?;
+
+        let mut arms = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            let (patterns, guard) = self.match_arm_head()?;
+            let body = self.expression()?;
+            arms.push(MatchArm {
+                patterns,
+                guard,
+                body,
+            });
+            if !self.is_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&TokenType::RightBrace, "Expect '}' after match arms.")?;
+
+        Ok(Expr::Match(ast::Match {
+            keyword,
+            subject: Box::new(subject),
+            arms,
+        }))
+    }
+
+    fn match_subject(&mut self) -> Result<(Token, Expr), Error> {
+        let keyword = self.previous();
+        self.consume(&TokenType::LeftParen, "Expect '(' after 'match'.")?;
+        let subject = self.expression()?;
+        self.consume(&TokenType::RightParen, "Expect ')' after match value.")?;
+        self.consume(&TokenType::LeftBrace, "Expect '{' before match arms.")?;
+
+        Ok((keyword, subject))
+    }
+
+    /// pattern ( "|" pattern )* ( "if" expression )? "=>"
+    fn match_arm_head(&mut self) -> Result<(Vec<Pattern>, Option<Expr>), Error> {
+        let mut patterns = vec![self.pattern()?];
+        while self.is_match(&[TokenType::Pipe]) {
+            patterns.push(self.pattern()?);
+        }
+
+        let mut guard = None;
+        if self.is_match(&[TokenType::If]) {
+            guard = Some(self.expression()?);
+        }
+        self.consume(&TokenType::FatArrow, "Expect '=>' after match pattern.")?;
+
+        Ok((patterns, guard))
+    }
+
+    /// pattern        → "_" | literal ( ( ".." | "..=" ) literal )? ;
+    fn pattern(&mut self) -> Result<Pattern, Error> {
+        if self.check(&TokenType::Identifier) && self.peek().lexeme == "_" {
+            self.advance();
+            return Ok(Pattern::Wildcard);
+        }
+
+        let start = self.pattern_literal()?;
+        if self.is_match(&[TokenType::DotDot, TokenType::DotDotEqual]) {
+            let op = self.previous();
+            let end = self.pattern_literal()?;
+            if start.as_f64().is_none() || end.as_f64().is_none() {
+                error_message!("line:{} Range pattern bounds must be numbers.", op.line);
+            }
+            return Ok(Pattern::Range {
+                start,
+                end,
+                inclusive: op.ttype == TokenType::DotDotEqual,
+            });
+        }
+
+        Ok(Pattern::Literal(start))
+    }
+
+    fn pattern_literal(&mut self) -> Result<LiteralTypes, Error> {
+        if self.is_match(&[TokenType::Minus]) {
+            self.consume(&TokenType::Number, "Expect number after '-' in pattern.")?;
+            match self.previous().literal {
+                LiteralTypes::Int(n) => return Ok(LiteralTypes::Int(-n)),
+                LiteralTypes::Number(n) => return Ok(LiteralTypes::Number(-n)),
+                LiteralTypes::BigInt(n) => return Ok(LiteralTypes::integer(-&n)),
+                LiteralTypes::Decimal(n) => return Ok(LiteralTypes::Decimal(n.neg())),
+                _ => {}
+            }
+        }
+        if self.is_match(&[
+            TokenType::True,
+            TokenType::False,
+            TokenType::Nil,
+            TokenType::Number,
+            TokenType::String,
+        ]) {
+            return Ok(self.previous().literal);
+        }
+
+        Err(self.error("Expect a literal, range or '_' pattern."))
+    }
+
     fn for_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
         self.consume(&TokenType::LeftParen, "Expect '(' after 'for'.")?;
+        if self.check(&TokenType::Identifier)
+            && matches!(self.peek_next().ttype, TokenType::In | TokenType::Comma)
+        {
+            return self.for_in_statement(keyword);
+        }
 
-        let mut initializer;
+        let initializer;
         if self.is_match(&[TokenType::Semicolon]) {
             initializer = None;
         } else if self.is_match(&[TokenType::Var]) {
@@ -142,6 +662,33 @@ impl Parser {
         Ok(body)
     }
 
+    /// for_in_stmt    → "for" "(" IDENTIFIER ( "," IDENTIFIER )? "in" expression ")" statement ;
+    fn for_in_statement(&mut self, keyword: Token) -> Result<Stmt, Error> {
+        let mut names = Vec::new();
+        loop {
+            self.consume(&TokenType::Identifier, "Expect loop variable name.")?;
+            names.push(self.previous());
+            if names.len() == 2 || !self.is_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&TokenType::In, "Expect 'in' after loop variables.")?;
+        for name in names.iter() {
+            self.declare(&Target::Name(name.clone()), false)?;
+        }
+        let iterable = self.expression()?;
+        self.consume(&TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        let body = self.statement()?;
+
+        Ok(Stmt::ForIn(stmt::ForIn {
+            keyword,
+            names,
+            iterable,
+            body: Box::new(body),
+        }))
+    }
+
     fn while_statement(&mut self) -> Result<Stmt, Error> {
         self.consume(&TokenType::LeftParen, "Expect '(' after 'while'.")?;
         let condition = self.expression()?;
@@ -185,10 +732,14 @@ impl Parser {
     }
 
     fn print_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
         let expression = self.expression()?;
         self.consume(&TokenType::Semicolon, "Expect ';' after value.")?;
 
-        Ok(Stmt::Print(Print { expression }))
+        Ok(Stmt::Print(Print {
+            keyword,
+            expression,
+        }))
     }
     fn expression_statement(&mut self) -> Result<Stmt, Error> {
         let expression = self.expression()?;
@@ -202,25 +753,118 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> Result<Expr, Error> {
-        let expr = self.or()?;
+        // `(a, b) = ...` starts out like a tuple or list, so try it as a pattern first
+        if self.check(&TokenType::LeftParen) || self.check(&TokenType::LeftBracket) {
+            let start = self.current;
+            if let Ok(target) = self.target() {
+                if self.is_match(&[TokenType::Equal]) {
+                    self.check_assignable(&target)?;
+                    let value = self.assignment()?;
+                    return Ok(Expr::Destructure(ast::Destructure {
+                        target,
+                        value: Box::new(value),
+                    }));
+                }
+            }
+            self.current = start;
+        }
+
+        let expr = self.conditional()?;
 
         if self.is_match(&[TokenType::Equal]) {
             let equal = self.previous();
             let value = self.assignment()?;
 
             if let Expr::Variable(Variable { identifier, .. }) = expr {
+                self.check_assignable(&Target::Name(identifier.clone()))?;
                 return Ok(Expr::Assign(Assign {
                     name: identifier,
                     value: Box::new(value),
                 }));
             }
+            if let Expr::Index(ast::Index {
+                object,
+                bracket,
+                index,
+            }) = expr
+            {
+                return Ok(Expr::IndexSet(ast::IndexSet {
+                    object,
+                    bracket,
+                    index,
+                    value: Box::new(value),
+                }));
+            }
 
             return Err(self.token_error(&equal, "Invalid assignment target."));
         }
 
+        if self.is_match(&[
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+            TokenType::PercentEqual,
+        ]) {
+            let op = self.previous();
+            let value = self.assignment()?;
+            return self.compound(expr, op, value, false);
+        }
+
         Ok(expr)
     }
 
+    /// Build the update of `target` by the compound operator or `++`/`--` token `op`.
+    fn compound(&self, target: Expr, op: Token, value: Expr, postfix: bool) -> Result<Expr, Error> {
+        if !matches!(target, Expr::Variable(_) | Expr::Index(_)) {
+            return Err(self.token_error(&op, "Invalid assignment target."));
+        }
+        if let Expr::Variable(variable) = &target {
+            self.check_assignable(&Target::Name(variable.identifier.clone()))?;
+        }
+
+        let ttype = match op.ttype {
+            TokenType::PlusEqual | TokenType::PlusPlus => TokenType::Plus,
+            TokenType::MinusEqual | TokenType::MinusMinus => TokenType::Minus,
+            TokenType::StarEqual => TokenType::Star,
+            TokenType::SlashEqual => TokenType::Slash,
+            TokenType::PercentEqual => TokenType::Percent,
+            _ => return Err(self.token_error(&op, "Unknown compound operator.")),
+        };
+
+        Ok(Expr::Compound(ast::Compound {
+            target: Box::new(target),
+            op: Token { ttype, ..op },
+            value: Box::new(value),
+            postfix,
+        }))
+    }
+
+    fn increment(&self) -> Expr {
+        Expr::Literal(LiteralTypes::Int(1))
+    }
+
+    /// conditional    → or ( "?" expression ":" conditional )? ;
+    fn conditional(&mut self) -> Result<Expr, Error> {
+        let condition = self.or()?;
+
+        if self.is_match(&[TokenType::Question]) {
+            let then_branch = self.expression()?;
+            self.consume(
+                &TokenType::Colon,
+                "Expect ':' after then branch of conditional expression.",
+            )?;
+            let else_branch = self.conditional()?;
+            return Ok(Expr::Conditional(ast::Conditional {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            }));
+        }
+
+        Ok(condition)
+    }
+
     fn or(&mut self) -> Result<Expr, Error> {
         let mut expr = self.and()?;
         while self.is_match(&[TokenType::Or]) {
@@ -236,11 +880,11 @@ impl Parser {
         Ok(expr)
     }
     fn and(&mut self) -> Result<Expr, Error> {
-        let mut expr = self.equality()?;
+        let mut expr = self.bit_or()?;
 
         while self.is_match(&[TokenType::And]) {
             let op = self.previous();
-            let right = self.equality()?;
+            let right = self.bit_or()?;
             expr = Expr::Logical(Logical {
                 left: Box::new(expr),
                 op,
@@ -251,6 +895,51 @@ impl Parser {
         Ok(expr)
     }
 
+    /// bit_or         → bit_xor ( "|" bit_xor )* ;
+    fn bit_or(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.bit_xor()?;
+        while self.is_match(&[TokenType::Pipe]) {
+            let op = self.previous();
+            let right = self.bit_xor()?;
+            expr = Expr::Binary(Binary {
+                op,
+                left: Box::new(expr),
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
+    /// bit_xor        → bit_and ( "^" bit_and )* ;
+    fn bit_xor(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.bit_and()?;
+        while self.is_match(&[TokenType::Caret]) {
+            let op = self.previous();
+            let right = self.bit_and()?;
+            expr = Expr::Binary(Binary {
+                op,
+                left: Box::new(expr),
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
+    /// bit_and        → equality ( "&" equality )* ;
+    fn bit_and(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.equality()?;
+        while self.is_match(&[TokenType::Amp]) {
+            let op = self.previous();
+            let right = self.equality()?;
+            expr = Expr::Binary(Binary {
+                op,
+                left: Box::new(expr),
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
     /// equality       → comparison ( ( "!=" | "==" ) comparison )* ;
     fn equality(&mut self) -> Result<Expr, Error> {
         let mut expr = self.comparison()?;
@@ -267,9 +956,9 @@ impl Parser {
         Ok(expr)
     }
 
-    /// comparison     → term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
+    /// comparison     → range ( ( ">" | ">=" | "<" | "<=" ) range )* ;
     fn comparison(&mut self) -> Result<Expr, Error> {
-        let mut expr = self.term()?;
+        let mut expr = self.range()?;
         while self.is_match(&[
             TokenType::Greater,
             TokenType::GreaterEqual,
@@ -277,7 +966,7 @@ impl Parser {
             TokenType::LessEqual,
         ]) {
             let op = self.previous();
-            let right = self.term()?;
+            let right = self.range()?;
             expr = Expr::Comparison(Comparison {
                 op,
                 left: Box::new(expr),
@@ -287,6 +976,46 @@ impl Parser {
         Ok(expr)
     }
 
+    /// range          → shift ( ( ".." | "..=" ) shift ( "step" shift )? )? ;
+    ///
+    /// `step` is only special right after a range, so it stays usable as a name.
+    fn range(&mut self) -> Result<Expr, Error> {
+        let start = self.shift()?;
+        if !self.is_match(&[TokenType::DotDot, TokenType::DotDotEqual]) {
+            return Ok(start);
+        }
+        let op = self.previous();
+        let end = self.shift()?;
+
+        let mut step = None;
+        if self.check(&TokenType::Identifier) && self.peek().lexeme == "step" {
+            self.advance();
+            step = Some(Box::new(self.shift()?));
+        }
+
+        Ok(Expr::Range(ast::Range {
+            start: Box::new(start),
+            op,
+            end: Box::new(end),
+            step,
+        }))
+    }
+
+    /// shift          → term ( ( "<<" | ">>" ) term )* ;
+    fn shift(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.term()?;
+        while self.is_match(&[TokenType::LessLess, TokenType::GreaterGreater]) {
+            let op = self.previous();
+            let right = self.term()?;
+            expr = Expr::Binary(Binary {
+                op,
+                left: Box::new(expr),
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
     /// term           → factor ( ( "-" | "+" ) factor )* ;
     fn term(&mut self) -> Result<Expr, Error> {
         let mut expr = self.factor()?;
@@ -302,10 +1031,15 @@ impl Parser {
         Ok(expr)
     }
 
-    /// factor         → unary ( ( "/" | "*" ) unary )*
+    /// factor         → unary ( ( "/" | "*" | "%" | "~/" ) unary )*
     fn factor(&mut self) -> Result<Expr, Error> {
         let mut expr = self.unary()?;
-        while self.is_match(&[TokenType::Slash, TokenType::Star]) {
+        while self.is_match(&[
+            TokenType::Slash,
+            TokenType::Star,
+            TokenType::Percent,
+            TokenType::TildeSlash,
+        ]) {
             let op = self.previous();
             let right = self.unary()?;
             expr = Expr::Binary(Binary {
@@ -318,10 +1052,10 @@ impl Parser {
         Ok(expr)
     }
 
-    /// unary → ( "!" | "-" ) unary | primary
+    /// unary → ( "!" | "-" | "~" | "++" | "--" ) unary | power
     fn unary(&mut self) -> Result<Expr, Error> {
         use ast::Unary;
-        if self.is_match(&[TokenType::Bang, TokenType::Minus]) {
+        if self.is_match(&[TokenType::Bang, TokenType::Minus, TokenType::Tilde]) {
             let op = self.previous();
             let right = self.unary()?;
             return Ok(Expr::Unary(Unary {
@@ -329,9 +1063,130 @@ impl Parser {
                 right: Box::new(right),
             }));
         }
+        if self.is_match(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let op = self.previous();
+            let target = self.unary()?;
+            return self.compound(target, op, self.increment(), false);
+        }
+
+        self.power()
+    }
+
+    /// power → postfix ( "**" unary )?
+    ///
+    /// The right operand goes back through `unary`, which makes `**` right-associative
+    /// and lets `-2 ** 2` parse as `-(2 ** 2)`.
+    fn power(&mut self) -> Result<Expr, Error> {
+        let expr = self.postfix()?;
+
+        if self.is_match(&[TokenType::StarStar]) {
+            let op = self.previous();
+            let right = self.unary()?;
+            return Ok(Expr::Binary(Binary {
+                left: Box::new(expr),
+                op,
+                right: Box::new(right),
+            }));
+        }
+
+        Ok(expr)
+    }
+
+    /// postfix → call ( "++" | "--" )?
+    fn postfix(&mut self) -> Result<Expr, Error> {
+        let expr = self.call()?;
+
+        if self.is_match(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let op = self.previous();
+            return self.compound(expr, op, self.increment(), true);
+        }
+
+        Ok(expr)
+    }
+    /// call → primary ( "(" arguments? ")" | "[" subscript "]" | "." IDENTIFIER )*
+    fn call(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.is_match(&[TokenType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.is_match(&[TokenType::LeftBracket]) {
+                expr = self.finish_subscript(expr)?;
+            } else if self.is_match(&[TokenType::Dot]) {
+                let name = self.property_name()?;
+                expr = Expr::Get(ast::Get {
+                    object: Box::new(expr),
+                    name,
+                });
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, Error> {
+        let arguments = self.arguments(&TokenType::RightParen)?;
+        self.consume(&TokenType::RightParen, "Expect ')' after arguments.")?;
+        if arguments.len() > 255 {
+            return Err(self.token_error(&self.previous(), "Can't have more than 255 arguments."));
+        }
+
+        Ok(Expr::Call(ast::Call {
+            callee: Box::new(callee),
+            paren: self.previous(),
+            arguments,
+        }))
+    }
+
+    /// Comma separated expressions up to (not including) `end`, allowing a trailing comma.
+    fn arguments(&mut self, end: &TokenType) -> Result<Vec<Expr>, Error> {
+        let mut arguments = Vec::new();
+        while !self.check(end) {
+            arguments.push(self.expression()?);
+            if !self.is_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    /// subscript → expression | expression? ":" expression?
+    fn finish_subscript(&mut self, object: Expr) -> Result<Expr, Error> {
+        let bracket = self.previous();
+
+        let mut start = None;
+        if !self.check(&TokenType::Colon) {
+            start = Some(Box::new(self.expression()?));
+        }
+
+        if self.is_match(&[TokenType::Colon]) {
+            let mut end = None;
+            if !self.check(&TokenType::RightBracket) {
+                end = Some(Box::new(self.expression()?));
+            }
+            self.consume(&TokenType::RightBracket, "Expect ']' after slice.")?;
+            return Ok(Expr::Slice(ast::Slice {
+                object: Box::new(object),
+                bracket,
+                start,
+                end,
+            }));
+        }
 
-        self.primary()
+        self.consume(&TokenType::RightBracket, "Expect ']' after index.")?;
+        match start {
+            Some(index) => Ok(Expr::Index(ast::Index {
+                object: Box::new(object),
+                bracket,
+                index,
+            })),
+            None => Err(self.token_error(&bracket, "Expect index.")),
+        }
     }
+
     fn primary(&mut self) -> Result<Expr, Error> {
         if self.is_match(&[
             TokenType::True,
@@ -350,16 +1205,74 @@ impl Parser {
         }
 
         if self.is_match(&[TokenType::LeftParen]) {
+            let paren = self.previous();
+            if self.is_match(&[TokenType::RightParen]) {
+                let elements = Vec::new();
+                return Ok(Expr::Tuple(ast::Tuple { paren, elements }));
+            }
             let expr = self.expression()?;
+            if self.is_match(&[TokenType::Comma]) {
+                let mut elements = vec![expr];
+                elements.extend(self.arguments(&TokenType::RightParen)?);
+                self.consume(&TokenType::RightParen, "Expect ')' after tuple elements.")?;
+                return Ok(Expr::Tuple(ast::Tuple { paren, elements }));
+            }
             self.consume(&TokenType::RightParen, "Expect ')' after expression.")?;
             return Ok(Expr::Grouping(ast::Grouping {
                 expr: Box::new(expr),
             }));
         }
 
+        if self.is_match(&[TokenType::Match]) {
+            return self.match_expression();
+        }
+
+        if self.is_match(&[TokenType::LeftBracket]) {
+            let bracket = self.previous();
+            let elements = self.arguments(&TokenType::RightBracket)?;
+            self.consume(&TokenType::RightBracket, "Expect ']' after list elements.")?;
+            return Ok(Expr::List(ast::List { bracket, elements }));
+        }
+
+        // a `{` starting a statement is a block, so this only sees maps
+        if self.is_match(&[TokenType::LeftBrace]) {
+            return self.map();
+        }
+
         Err(self.error("expect a expression"))
     }
 
+    /// map → "{" ( entry ( "," entry )* ","? )? "}" ;
+    fn map(&mut self) -> Result<Expr, Error> {
+        let brace = self.previous();
+        let mut entries = Vec::new();
+        while !self.check(&TokenType::RightBrace) {
+            let key = self.expression()?;
+            self.consume(&TokenType::Colon, "Expect ':' after map key.")?;
+            entries.push((key, self.expression()?));
+            if !self.is_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(&TokenType::RightBrace, "Expect '}' after map entries.")?;
+
+        Ok(Expr::Map(ast::Map { brace, entries }))
+    }
+
+    /// The name after a `.`, where keywords read as plain names so that `re.match` works.
+    fn property_name(&mut self) -> Result<Token, Error> {
+        let token = self.peek();
+        let is_word = token
+            .lexeme
+            .starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+        if token.ttype != TokenType::String && is_word {
+            let mut name = token.clone();
+            name.ttype = TokenType::Identifier;
+            self.advance();
+            return Ok(name);
+        }
+        Err(self.error("Expect property name after '.'."))
+    }
     fn consume(&mut self, expect: &TokenType, message: &str) -> Result<(), Error> {
         if self.check(expect) {
             self.advance();
@@ -406,6 +1319,9 @@ impl Parser {
     fn peek(&self) -> &Token {
         &self.tokens[self.current]
     }
+    fn peek_next(&self) -> &Token {
+        &self.tokens[(self.current + 1).min(self.tokens.len() - 1)]
+    }
     fn previous(&self) -> Token {
         self.tokens[self.current - 1].clone()
     }
@@ -443,16 +1359,44 @@ mod tests {
             ast,
             Expr::Binary(Binary {
                 left: Box::new(Expr::Binary(Binary {
-                    left: Box::new(Expr::Literal(LiteralTypes::Number(1.0))),
+                    left: Box::new(Expr::Literal(LiteralTypes::Int(1))),
                     op: normal_token(TokenType::Plus, "+"),
                     right: Box::new(Expr::Binary(Binary {
-                        left: Box::new(Expr::Literal(LiteralTypes::Number(2.0))),
+                        left: Box::new(Expr::Literal(LiteralTypes::Int(2))),
                         op: normal_token(TokenType::Star, "*"),
-                        right: Box::new(Expr::Literal(LiteralTypes::Number(3.0))),
+                        right: Box::new(Expr::Literal(LiteralTypes::Int(3))),
                     }))
                 })),
                 op: normal_token(TokenType::Minus, "-"),
-                right: Box::new(Expr::Literal(LiteralTypes::Number(4.0)))
+                right: Box::new(Expr::Literal(LiteralTypes::Int(4)))
+            })
+        )
+    }
+
+    #[test]
+    fn test_conditional_right_assoc() {
+        let mut parser = Parser::new("a ? b : c ? d : e");
+        let ast = parser.expression().unwrap();
+        let var = |name: &str| {
+            Box::new(Expr::Variable(Variable {
+                identifier: Token::new(
+                    TokenType::Identifier,
+                    name.to_string(),
+                    LiteralTypes::String(name.to_string()),
+                    1,
+                ),
+            }))
+        };
+        assert_eq!(
+            ast,
+            Expr::Conditional(ast::Conditional {
+                condition: var("a"),
+                then_branch: var("b"),
+                else_branch: Box::new(Expr::Conditional(ast::Conditional {
+                    condition: var("c"),
+                    then_branch: var("d"),
+                    else_branch: var("e"),
+                })),
             })
         )
     }
@@ -0,0 +1,75 @@
+//! Modules loaded with `import`: each is a script run once in its own top-level scope.
+
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::lexer::{LiteralTypes, Token};
+
+use super::env::Environment;
+
+#[derive(Debug)]
+pub struct Module {
+    /// The canonical path the module was loaded from, or the name of a built-in one.
+    pub path: PathBuf,
+    /// The module's top-level scope; every name defined there is exported, but not the
+    /// globals it encloses.
+    pub env: Rc<RefCell<Environment>>,
+}
+
+/// A module is loaded once per path, so the path identifies it.
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl Module {
+    /// `module.name`, or an import of `name`.
+    pub fn get(&self, name: &Token) -> Result<LiteralTypes, String> {
+        self.env
+            .borrow()
+            .get_local(name)
+            .ok_or_else(|| format!("Module '{}' has no export '{}'.", self.name(), name.lexeme))
+    }
+
+    pub fn name(&self) -> String {
+        file_name(&self.path)
+    }
+}
+
+/// Find the file an `import` refers to: next to the importing script first (or in the
+/// working directory when there is none), then in each directory of `search_path`.
+pub fn resolve(
+    spec: &str,
+    importer: Option<&Path>,
+    search_path: &[PathBuf],
+) -> Result<PathBuf, String> {
+    let base = importer
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    std::iter::once(base)
+        .chain(search_path.iter().cloned())
+        .map(|dir| dir.join(spec))
+        .find(|candidate| candidate.is_file())
+        .and_then(|found| found.canonicalize().ok())
+        .ok_or_else(|| format!("Can't find module '{}'.", spec))
+}
+
+/// How a cycle looks in an error: `a.lox -> b.lox -> a.lox`.
+pub fn describe_cycle(chain: &[PathBuf], again: &Path) -> String {
+    let start = chain.iter().position(|path| path == again).unwrap_or(0);
+    let mut names: Vec<String> = chain[start..].iter().map(|path| file_name(path)).collect();
+    names.push(file_name(again));
+    format!("Circular import: {}.", names.join(" -> "))
+}
+
+fn file_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => path.display().to_string(),
+    }
+}
@@ -0,0 +1,187 @@
+//! The core natives every interpreter starts with, and the built-in modules.
+
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use crate::{lexer::LiteralTypes, number::BigInt};
+
+use super::{
+    callable::NativeError, exception::ErrorKind, format, fs, inter::Interpreter, json, math, re,
+    string, time,
+};
+
+pub fn define_core(interpreter: &mut Interpreter) {
+    interpreter.define_native("clock", 0..=0, |interpreter, _| {
+        let now = interpreter.clock().now();
+        Ok(LiteralTypes::Number(now as f64 / 1000.0))
+    });
+    interpreter.define_native("len", 1..=1, |_, args| len(&args[0]));
+    interpreter.define_native("str", 1..=1, |_, args| {
+        Ok(LiteralTypes::String(args[0].to_text()))
+    });
+    interpreter.define_native("num", 1..=1, |_, args| to_number(&args[0]));
+    interpreter.define_native("type", 1..=1, |_, args| {
+        Ok(LiteralTypes::String(args[0].type_name().to_string()))
+    });
+    interpreter.define_native("format", 1..=usize::MAX, |_, args| {
+        let LiteralTypes::String(template) = &args[0] else {
+            return Err(NativeError::type_error(format!(
+                "Format template must be a string, got {}.",
+                args[0].type_name()
+            )));
+        };
+        Ok(LiteralTypes::String(format::format(template, &args[1..])?))
+    });
+    interpreter.define_native("from_char_code", 1..=1, |_, args| {
+        Ok(string::from_char_code(&args[0])?)
+    });
+    interpreter.define_native("input", 0..=1, |interpreter, args| {
+        if let Some(prompt) = args.first() {
+            let stdout = &mut interpreter.output().stdout;
+            write!(stdout, "{}", prompt.to_text())
+                .and_then(|_| stdout.flush())
+                .map_err(|err| {
+                    NativeError::Error(ErrorKind::Io, format!("Can't write output: {}.", err))
+                })?;
+        }
+        read_line(interpreter)
+    });
+    interpreter.define_native("read_line", 0..=0, |interpreter, _| read_line(interpreter));
+    interpreter.define_native("read_all_stdin", 0..=0, |interpreter, _| {
+        let text = interpreter.process().read_all().map_err(stdin_error)?;
+        Ok(LiteralTypes::String(text))
+    });
+    interpreter.define_native("args", 0..=0, |interpreter, _| {
+        let args = interpreter.process().args.iter();
+        let args = args.map(|arg| LiteralTypes::String(arg.clone())).collect();
+        Ok(LiteralTypes::List(Rc::new(RefCell::new(args))))
+    });
+    interpreter.define_native("env", 1..=1, |interpreter, args| {
+        let LiteralTypes::String(name) = &args[0] else {
+            return Err(NativeError::type_error(format!(
+                "Variable name must be a string, got {}.",
+                args[0].type_name()
+            )));
+        };
+        let value = interpreter.process().var(name);
+        Ok(value.map_or(LiteralTypes::Nil, LiteralTypes::String))
+    });
+    interpreter.define_native("exit", 0..=1, |_, args| {
+        let code = match args.first() {
+            None => 0,
+            Some(&LiteralTypes::Int(code)) => i32::try_from(code).map_err(|_| {
+                NativeError::Error(
+                    ErrorKind::Arithmetic,
+                    format!("Exit code {} is out of range.", code),
+                )
+            })?,
+            Some(other) => {
+                return Err(NativeError::type_error(format!(
+                    "Exit code must be an int, got {}.",
+                    other.type_name()
+                )))
+            }
+        };
+        Err(NativeError::Exit(code))
+    });
+    interpreter.define_native("assert", 1..=2, |_, args| {
+        if args[0].is_true() {
+            return Ok(LiteralTypes::Nil);
+        }
+        let message = match args.get(1) {
+            Some(message) => message.to_text(),
+            None => "Assertion failed.".to_string(),
+        };
+        Err(NativeError::Error(ErrorKind::Assertion, message))
+    });
+
+    interpreter.define_module("math", math::exports());
+    interpreter.define_module("fs", fs::exports());
+    interpreter.define_module("json", json::exports());
+    interpreter.define_module("re", re::exports());
+    interpreter.define_module("time", time::exports());
+}
+
+fn len(value: &LiteralTypes) -> Result<LiteralTypes, NativeError> {
+    let len = match value {
+        LiteralTypes::String(str) => str.chars().count(),
+        LiteralTypes::List(list) => list.borrow().len(),
+        LiteralTypes::Tuple(items) => items.len(),
+        LiteralTypes::Map(map) => map.borrow().len(),
+        _ => {
+            return Err(NativeError::type_error(format!(
+                "Can't take the length of a {}.",
+                value.type_name()
+            )))
+        }
+    };
+    Ok(LiteralTypes::Int(len as i64))
+}
+
+/// Numbers as they are; strings parsed as an int when they are whole, a float otherwise.
+fn to_number(value: &LiteralTypes) -> Result<LiteralTypes, NativeError> {
+    let text = match value {
+        LiteralTypes::String(text) => text.trim(),
+        _ if value.as_f64().is_some() => return Ok(value.clone()),
+        _ => {
+            return Err(NativeError::type_error(format!(
+                "Can't convert a {} to a number.",
+                value.type_name()
+            )))
+        }
+    };
+
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        if let Some(int) = BigInt::parse(text.trim_start_matches('+')) {
+            return Ok(LiteralTypes::integer(int));
+        }
+    }
+    // only plain decimal notation, so words like "inf" and "nan" are rejected
+    let numeric = text.bytes().any(|b| b.is_ascii_digit())
+        && text
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b));
+    match text.parse::<f64>() {
+        Ok(num) if numeric => Ok(LiteralTypes::Number(num)),
+        _ => Err(NativeError::type_error(format!(
+            "Can't convert '{}' to a number.",
+            text
+        ))),
+    }
+}
+
+fn read_line(interpreter: &mut Interpreter) -> Result<LiteralTypes, NativeError> {
+    match interpreter.process().read_line() {
+        Ok(Some(line)) => Ok(LiteralTypes::String(line)),
+        Ok(None) => Ok(LiteralTypes::Nil),
+        Err(err) => Err(stdin_error(err)),
+    }
+}
+
+fn stdin_error(err: io::Error) -> NativeError {
+    NativeError::Error(ErrorKind::Io, format!("Can't read stdin: {}.", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_from_strings() {
+        let num = |text: &str| to_number(&LiteralTypes::String(text.into()));
+        assert_eq!(num(" 42 "), Ok(LiteralTypes::Int(42)));
+        assert_eq!(num("+7"), Ok(LiteralTypes::Int(7)));
+        assert_eq!(num("-2.5e1"), Ok(LiteralTypes::Number(-25.0)));
+        assert_eq!(
+            num("123456789012345678901234567890").unwrap().stringify(),
+            "123456789012345678901234567890"
+        );
+        for bad in ["", "abc", "inf", "NaN", "1.2.3", "-"] {
+            assert!(num(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Where scripts' output goes: `print` and `input()` prompts to stdout, warnings and
+//! uncaught errors to stderr. Embedders can point either at any writer or a callback.
+
+use std::io::{self, Write};
+
+pub struct Output {
+    pub stdout: Box<dyn Write>,
+    pub stderr: Box<dyn Write>,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self {
+            stdout: Box::new(io::stdout()),
+            stderr: Box::new(io::stderr()),
+        }
+    }
+}
+
+/// A writer that hands what is written to a function a line at a time, so callers see
+/// whole lines however the text was split into writes. A flush hands over any partial
+/// line, as does dropping the writer.
+pub struct Callback<F: FnMut(&str)> {
+    callback: F,
+    /// Bytes of the current line, kept as bytes so a char split between writes survives.
+    line: Vec<u8>,
+}
+
+impl<F: FnMut(&str)> Callback<F> {
+    pub fn new(callback: F) -> Self {
+        Self {
+            callback,
+            line: Vec::new(),
+        }
+    }
+
+    fn emit(&mut self, end: usize) {
+        let line: Vec<u8> = self.line.drain(..end).collect();
+        (self.callback)(&String::from_utf8_lossy(&line));
+    }
+}
+
+impl<F: FnMut(&str)> Write for Callback<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.line.extend_from_slice(buf);
+        while let Some(newline) = self.line.iter().position(|b| *b == b'\n') {
+            self.emit(newline + 1);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            self.emit(self.line.len());
+        }
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&str)> Drop for Callback<F> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hands_over_whole_lines() {
+        let mut calls = Vec::new();
+        {
+            let mut callback = Callback::new(|text: &str| calls.push(text.to_string()));
+            let bytes = "\"héllo\"\n1\npart".as_bytes();
+            // split inside the `é`
+            callback.write_all(&bytes[..3]).unwrap();
+            callback.write_all(&bytes[3..]).unwrap();
+            callback.write_all(b"ial").unwrap();
+        }
+        assert_eq!(calls, ["\"héllo\"\n", "1\n", "partial"]);
+    }
+}
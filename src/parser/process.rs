@@ -0,0 +1,69 @@
+//! What a script sees of the process running it: its arguments, environment variables
+//! and stdin. Embedders can replace each source, e.g. to feed a script canned input.
+
+use std::io::{self, BufRead, BufReader, Read};
+
+type EnvSource = dyn Fn(&str) -> Option<String>;
+
+pub struct Process {
+    /// The arguments after the script path.
+    pub args: Vec<String>,
+    pub env: Box<EnvSource>,
+    pub stdin: Box<dyn BufRead>,
+}
+
+impl Default for Process {
+    fn default() -> Self {
+        Self {
+            args: Vec::new(),
+            env: Box::new(|name| std::env::var(name).ok()),
+            stdin: Box::new(BufReader::new(io::stdin())),
+        }
+    }
+}
+
+impl Process {
+    pub fn var(&self, name: &str) -> Option<String> {
+        (self.env)(name)
+    }
+
+    /// The next line of stdin without its line ending, or `None` at the end of input.
+    pub fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.stdin.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Some(line))
+    }
+
+    /// Whatever is left of stdin.
+    pub fn read_all(&mut self) -> io::Result<String> {
+        let mut text = String::new();
+        self.stdin.read_to_string(&mut text)?;
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_lines_then_the_rest() {
+        let mut process = Process {
+            stdin: Box::new("one\r\ntwo\nthree\nfour".as_bytes()),
+            ..Process::default()
+        };
+        assert_eq!(process.read_line().unwrap(), Some("one".to_string()));
+        assert_eq!(process.read_line().unwrap(), Some("two".to_string()));
+        assert_eq!(process.read_all().unwrap(), "three\nfour");
+        assert_eq!(process.read_line().unwrap(), None);
+        assert_eq!(process.read_all().unwrap(), "");
+    }
+}
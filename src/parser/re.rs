@@ -0,0 +1,243 @@
+//! The `re` module and the methods of compiled patterns.
+//!
+//! `re.compile(pattern)` returns a regex value with the same methods as the module:
+//! `re.find_all(pattern, text)` is `re.compile(pattern).find_all(text)`. Patterns passed
+//! as strings are compiled once and cached.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    lexer::LiteralTypes,
+    regex::{Captures, Regex},
+};
+
+use super::{
+    callable::{self, Callable, NativeError},
+    exception::ErrorKind,
+    Map,
+};
+
+/// How many string patterns are kept compiled before the cache starts over.
+const CACHE_SIZE: usize = 128;
+
+type Cache = Rc<RefCell<HashMap<String, Rc<Regex>>>>;
+
+pub fn exports() -> Vec<(&'static str, LiteralTypes)> {
+    let cache = Cache::default();
+    let mut exports = Vec::new();
+
+    let compile_cache = cache.clone();
+    let compile = Callable::native("compile", 1..=1, move |_, args| {
+        Ok(LiteralTypes::Regex(regex(&compile_cache, &args[0])?))
+    });
+    exports.push(("compile", compile));
+
+    for name in ["match", "find_all", "replace", "split"] {
+        let cache = cache.clone();
+        // one more argument than the method, for the pattern
+        let arity = arity(name).unwrap_or(0..=0);
+        let arity = arity.start() + 1..=arity.end() + 1;
+        let function = Callable::native(name, arity, move |_, mut args| {
+            let regex = regex(&cache, &args.remove(0))?;
+            Ok(call_method(&regex, name, args)?)
+        });
+        exports.push((name, function));
+    }
+    exports
+}
+
+fn arity(name: &str) -> Option<std::ops::RangeInclusive<usize>> {
+    match name {
+        "match" | "find_all" | "split" => Some(1..=1),
+        "replace" => Some(2..=3),
+        _ => None,
+    }
+}
+
+/// A regex argument as it is, or a string pattern compiled through `cache`.
+fn regex(cache: &Cache, pattern: &LiteralTypes) -> Result<Rc<Regex>, NativeError> {
+    let pattern = match pattern {
+        LiteralTypes::Regex(regex) => return Ok(regex.clone()),
+        LiteralTypes::String(pattern) => pattern,
+        other => {
+            return Err(NativeError::type_error(format!(
+                "Pattern must be a string or regex, got {}.",
+                other.type_name()
+            )))
+        }
+    };
+    if let Some(regex) = cache.borrow().get(pattern) {
+        return Ok(regex.clone());
+    }
+
+    let regex =
+        Regex::new(pattern).map_err(|message| NativeError::Error(ErrorKind::Parse, message))?;
+    let regex = Rc::new(regex);
+    let mut cache = cache.borrow_mut();
+    if cache.len() == CACHE_SIZE {
+        cache.clear();
+    }
+    cache.insert(pattern.clone(), regex.clone());
+    Ok(regex)
+}
+
+/// `regex.pattern` and `regex.groups`.
+pub fn get(regex: &Regex, property: &str) -> Result<LiteralTypes, String> {
+    match property {
+        "pattern" => Ok(LiteralTypes::String(regex.pattern().to_string())),
+        "groups" => Ok(LiteralTypes::Int(regex.groups() as i64)),
+        _ => Err(format!("Undefined property '{}' on regex.", property)),
+    }
+}
+
+/// Call the method `name` on `regex`. Errors are plain messages; the interpreter
+/// attributes them to the call site.
+pub fn call_method(
+    regex: &Regex,
+    name: &str,
+    args: Vec<LiteralTypes>,
+) -> Result<LiteralTypes, String> {
+    let Some(arity) = arity(name) else {
+        return Err(format!("Undefined method '{}' on regex.", name));
+    };
+    callable::check_arity(&arity, args.len())?;
+    let LiteralTypes::String(text) = &args[0] else {
+        return Err(format!(
+            "Can only match against a string, got {}.",
+            args[0].type_name()
+        ));
+    };
+    let text: Vec<char> = text.chars().collect();
+
+    match name {
+        "match" => Ok(match regex.find_at(&text, 0) {
+            Some(captures) => match_map(&text, &captures),
+            None => LiteralTypes::Nil,
+        }),
+        "find_all" => {
+            let found = regex.find_all(&text).into_iter().map(|captures| {
+                let groups = captures[1..].iter().map(|span| span_text(&text, *span));
+                match captures.len() {
+                    1 => span_text(&text, captures[0]),
+                    2 => span_text(&text, captures[1]),
+                    _ => LiteralTypes::Tuple(groups.collect()),
+                }
+            });
+            Ok(list(found.collect()))
+        }
+        "replace" => {
+            let LiteralTypes::String(replacement) = &args[1] else {
+                return Err(format!(
+                    "Replacement must be a string, got {}.",
+                    args[1].type_name()
+                ));
+            };
+            let limit = match args.get(2) {
+                None | Some(LiteralTypes::Nil) => usize::MAX,
+                Some(LiteralTypes::Int(count)) if *count >= 0 => *count as usize,
+                Some(other) => {
+                    return Err(format!(
+                        "Replacement count must be a non-negative int, got {}.",
+                        other.stringify()
+                    ))
+                }
+            };
+
+            let mut out = String::new();
+            let mut copied = 0;
+            for captures in regex.find_all(&text).iter().take(limit) {
+                let (start, end) = captures[0].unwrap_or_default();
+                out.extend(&text[copied..start]);
+                expand(replacement, &text, captures, &mut out)?;
+                copied = end;
+            }
+            out.extend(&text[copied..]);
+            Ok(LiteralTypes::String(out))
+        }
+        "split" => {
+            let mut pieces = Vec::new();
+            let mut copied = 0;
+            for captures in regex.find_all(&text) {
+                let (start, end) = captures[0].unwrap_or_default();
+                // an empty match at either end separates nothing
+                if end == 0 || start == text.len() {
+                    continue;
+                }
+                pieces.push(LiteralTypes::String(text[copied..start].iter().collect()));
+                copied = end;
+            }
+            pieces.push(LiteralTypes::String(text[copied..].iter().collect()));
+            Ok(list(pieces))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// `{"text": ..., "start": ..., "end": ..., "groups": [...]}`, with char offsets and
+/// `nil` for groups that took no part in the match.
+fn match_map(text: &[char], captures: &Captures) -> LiteralTypes {
+    let (start, end) = captures[0].unwrap_or_default();
+    let groups = captures[1..]
+        .iter()
+        .map(|span| span_text(text, *span))
+        .collect();
+
+    let mut map = Map::default();
+    for (key, value) in [
+        ("text", span_text(text, captures[0])),
+        ("start", LiteralTypes::Int(start as i64)),
+        ("end", LiteralTypes::Int(end as i64)),
+        ("groups", list(groups)),
+    ] {
+        // string keys always hash
+        let _ = map.insert(LiteralTypes::String(key.to_string()), value);
+    }
+    LiteralTypes::Map(Rc::new(RefCell::new(map)))
+}
+
+/// Append `replacement` to `out` with `$n` replaced by group `n` and `$$` by `$`.
+fn expand(
+    replacement: &str,
+    text: &[char],
+    captures: &Captures,
+    out: &mut String,
+) -> Result<(), String> {
+    let mut chars = replacement.chars().peekable();
+    while let Some(char) = chars.next() {
+        if char != '$' {
+            out.push(char);
+            continue;
+        }
+        if chars.next_if_eq(&'$').is_some() {
+            out.push('$');
+            continue;
+        }
+        let mut digits = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            digits.push(digit);
+        }
+        if digits.is_empty() {
+            out.push('$');
+            continue;
+        }
+        let group = digits.parse::<usize>().unwrap_or(usize::MAX);
+        let Some(span) = captures.get(group) else {
+            return Err(format!("Replacement refers to missing group {}.", digits));
+        };
+        if let Some((start, end)) = span {
+            out.extend(&text[*start..*end]);
+        }
+    }
+    Ok(())
+}
+
+fn span_text(text: &[char], span: Option<(usize, usize)>) -> LiteralTypes {
+    match span {
+        Some((start, end)) => LiteralTypes::String(text[start..end].iter().collect()),
+        None => LiteralTypes::Nil,
+    }
+}
+
+fn list(items: Vec<LiteralTypes>) -> LiteralTypes {
+    LiteralTypes::List(Rc::new(RefCell::new(items)))
+}
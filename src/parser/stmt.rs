@@ -1,6 +1,6 @@
 use crate::lexer::Token;
 
-use super::ast::Expr;
+use super::ast::{Expr, MatchArm, Target};
 
 #[derive(Debug)]
 pub enum Stmt {
@@ -10,6 +10,12 @@ pub enum Stmt {
     Block(Block),
     If(If),
     While(While),
+    ForIn(ForIn),
+    Match(Match),
+    Enum(Enum),
+    Throw(Throw),
+    Try(Try),
+    Import(Import),
 }
 #[derive(Debug)]
 pub struct Expression {
@@ -17,11 +23,14 @@ pub struct Expression {
 }
 #[derive(Debug)]
 pub struct Print {
+    pub keyword: Token,
     pub expression: Expr,
 }
 #[derive(Debug)]
 pub struct Var {
-    pub name: Token,
+    pub target: Target,
+    /// Declared with `const` rather than `var`.
+    pub constant: bool,
     pub initializer: Expr,
 }
 
@@ -43,6 +52,61 @@ pub struct While {
     pub body: Box<Stmt>,
 }
 
+/// `for (value in iterable)` or `for (key, value in iterable)`
+#[derive(Debug)]
+pub struct ForIn {
+    pub keyword: Token,
+    pub names: Vec<Token>,
+    pub iterable: Expr,
+    pub body: Box<Stmt>,
+}
+
+#[derive(Debug)]
+pub struct Match {
+    pub keyword: Token,
+    pub subject: Expr,
+    pub arms: Vec<MatchArm<Stmt>>,
+}
+
+/// `enum Name { Plain, WithPayload(field, ...) }`
+#[derive(Debug)]
+pub struct Enum {
+    pub name: Token,
+    pub variants: Vec<(Token, Vec<Token>)>,
+}
+
+#[derive(Debug)]
+pub struct Throw {
+    pub keyword: Token,
+    pub value: Expr,
+}
+
+/// `try { } catch (name) { } finally { }`, with at least one of the two handlers.
+#[derive(Debug)]
+pub struct Try {
+    pub body: Vec<Stmt>,
+    /// The handler and the name it binds the error to, if it binds one.
+    pub catch: Option<(Option<Token>, Vec<Stmt>)>,
+    pub finally: Option<Vec<Stmt>>,
+}
+
+/// `import "path" as name;` or `from "path" import a, b;`
+#[derive(Debug)]
+pub struct Import {
+    pub keyword: Token,
+    /// The string literal naming the file.
+    pub path: Token,
+    pub names: ImportNames,
+}
+
+#[derive(Debug)]
+pub enum ImportNames {
+    /// Bind the whole module under one name.
+    Module(Token),
+    /// Bind these exports under their own names.
+    Exports(Vec<Token>),
+}
+
 pub trait Visitor<T> {
     fn visit_expression_stmt(&mut self, expr: &Expression) -> T;
     fn visit_print_stmt(&mut self, expr: &Print) -> T;
@@ -50,6 +114,12 @@ pub trait Visitor<T> {
     fn visit_block_stmt(&mut self, block: &Block) -> T;
     fn visit_if_stmt(&mut self, i: &If) -> T;
     fn visit_while_stmt(&mut self, w: &While) -> T;
+    fn visit_for_in_stmt(&mut self, f: &ForIn) -> T;
+    fn visit_match_stmt(&mut self, m: &Match) -> T;
+    fn visit_enum_stmt(&mut self, e: &Enum) -> T;
+    fn visit_throw_stmt(&mut self, t: &Throw) -> T;
+    fn visit_try_stmt(&mut self, t: &Try) -> T;
+    fn visit_import_stmt(&mut self, i: &Import) -> T;
 }
 
 impl Stmt {
@@ -61,6 +131,12 @@ impl Stmt {
             Stmt::Block(b) => visitor.visit_block_stmt(b),
             Stmt::If(i) => visitor.visit_if_stmt(i),
             Stmt::While(w) => visitor.visit_while_stmt(w),
+            Stmt::ForIn(f) => visitor.visit_for_in_stmt(f),
+            Stmt::Match(m) => visitor.visit_match_stmt(m),
+            Stmt::Enum(e) => visitor.visit_enum_stmt(e),
+            Stmt::Throw(t) => visitor.visit_throw_stmt(t),
+            Stmt::Try(t) => visitor.visit_try_stmt(t),
+            Stmt::Import(i) => visitor.visit_import_stmt(i),
         }
     }
 }
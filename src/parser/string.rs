@@ -0,0 +1,151 @@
+//! Built-in methods of string values: `s.split(",")`, `s.upper()`, ...
+//!
+//! Every offset and length counts chars, not bytes, so `"héllo".substr(1, 2)` is `"é"`.
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::lexer::LiteralTypes;
+
+use super::{
+    callable::{self, NativeError},
+    exception::ErrorKind,
+    list,
+};
+
+/// Longer results of `repeat` are surely mistakes, and would only eat memory.
+const MAX_REPEAT_CHARS: u64 = 10_000_000;
+
+/// Call the method `name` on `text`. Bad indexes are index errors, like those of
+/// `text[i]`; the interpreter attributes errors to the call site.
+pub fn call_method(
+    text: &str,
+    name: &str,
+    args: Vec<LiteralTypes>,
+) -> Result<LiteralTypes, NativeError> {
+    let arity = match name {
+        "len" | "trim" | "trim_start" | "trim_end" | "upper" | "lower" | "chars" => 0..=0,
+        "contains" | "starts_with" | "ends_with" | "join" | "repeat" => 1..=1,
+        "substr" | "index_of" => 1..=2,
+        "split" | "char_code" => 0..=1,
+        "replace" => 2..=2,
+        _ => return Err(format!("Undefined method '{}' on string.", name).into()),
+    };
+    callable::check_arity(&arity, args.len())?;
+
+    let string = |text: &str| Ok(LiteralTypes::String(text.to_string()));
+    match name {
+        "len" => Ok(LiteralTypes::Int(text.chars().count() as i64)),
+        "trim" => string(text.trim()),
+        "trim_start" => string(text.trim_start()),
+        "trim_end" => string(text.trim_end()),
+        "upper" => string(&text.to_uppercase()),
+        "lower" => string(&text.to_lowercase()),
+        "chars" => Ok(list(text.chars().map(|c| c.to_string()))),
+        "contains" => Ok(LiteralTypes::Bool(text.contains(text_arg(&args[0])?))),
+        "starts_with" => Ok(LiteralTypes::Bool(text.starts_with(text_arg(&args[0])?))),
+        "ends_with" => Ok(LiteralTypes::Bool(text.ends_with(text_arg(&args[0])?))),
+        "substr" => {
+            let chars: Vec<char> = text.chars().collect();
+            let (start, end) = list::slice_bounds(args.first(), args.get(1), chars.len())?;
+            string(&chars[start..end].iter().collect::<String>())
+        }
+        "index_of" => {
+            let needle = text_arg(&args[0])?;
+            let (from, _) = list::slice_bounds(args.get(1), None, text.chars().count())?;
+            let offset = text
+                .char_indices()
+                .nth(from)
+                .map_or(text.len(), |(at, _)| at);
+            let found = text[offset..].find(needle).map_or(-1, |at| {
+                (from + text[offset..offset + at].chars().count()) as i64
+            });
+            Ok(LiteralTypes::Int(found))
+        }
+        "split" => match args.first() {
+            None => Ok(list(text.split_whitespace().map(str::to_string))),
+            Some(separator) => match text_arg(separator)? {
+                "" => Err("Separator can't be empty.".to_string().into()),
+                separator => Ok(list(text.split(separator).map(str::to_string))),
+            },
+        },
+        "join" => {
+            let items = match &args[0] {
+                LiteralTypes::List(items) => items.borrow().clone(),
+                LiteralTypes::Tuple(items) => items.to_vec(),
+                other => {
+                    return Err(format!(
+                        "Can only join a list or tuple, got {}.",
+                        other.type_name()
+                    )
+                    .into())
+                }
+            };
+            let items: Vec<String> = items.iter().map(LiteralTypes::to_text).collect();
+            string(&items.join(text))
+        }
+        "replace" => string(&text.replace(text_arg(&args[0])?, text_arg(&args[1])?)),
+        "repeat" => match &args[0] {
+            LiteralTypes::Int(count) if *count >= 0 => {
+                let chars = text.chars().count() as u64;
+                if chars.saturating_mul(*count as u64) > MAX_REPEAT_CHARS {
+                    return Err(format!(
+                        "Can't repeat a string {} times, the result would be over {} chars.",
+                        count, MAX_REPEAT_CHARS
+                    )
+                    .into());
+                }
+                string(&text.repeat(*count as usize))
+            }
+            LiteralTypes::Int(count) => {
+                Err(format!("Can't repeat a string {} times.", count).into())
+            }
+            other => Err(format!("Repeat count must be an int, got {}.", other.type_name()).into()),
+        },
+        "char_code" => {
+            let len = text.chars().count();
+            let at = match args.first() {
+                Some(index) => list::position(index, len, "string")
+                    .map_err(|message| NativeError::Error(ErrorKind::Index, message))?,
+                None if len > 0 => 0,
+                None => return Err("Empty string has no char code.".to_string().into()),
+            };
+            let char = text.chars().nth(at).unwrap_or_default();
+            Ok(LiteralTypes::Int(char as i64))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// `from_char_code(code)`: the one-char string for a Unicode code point.
+pub fn from_char_code(code: &LiteralTypes) -> Result<LiteralTypes, String> {
+    let LiteralTypes::Int(code) = code else {
+        return Err(format!(
+            "Char code must be an int, got {}.",
+            code.type_name()
+        ));
+    };
+    u32::try_from(*code)
+        .ok()
+        .and_then(char::from_u32)
+        .map(|char| LiteralTypes::String(char.to_string()))
+        .ok_or_else(|| format!("{} is not a valid char code.", code))
+}
+
+/// The char at `index`, for `text[index]`.
+pub fn char_at(text: &str, index: &LiteralTypes) -> Result<LiteralTypes, String> {
+    let at = list::position(index, text.chars().count(), "string")?;
+    let char = text.chars().nth(at).unwrap_or_default();
+    Ok(LiteralTypes::String(char.to_string()))
+}
+
+fn text_arg(value: &LiteralTypes) -> Result<&str, String> {
+    match value {
+        LiteralTypes::String(text) => Ok(text),
+        other => Err(format!("Expected a string, got {}.", other.type_name())),
+    }
+}
+
+fn list(items: impl Iterator<Item = String>) -> LiteralTypes {
+    let items = items.map(LiteralTypes::String).collect();
+    LiteralTypes::List(Rc::new(RefCell::new(items)))
+}
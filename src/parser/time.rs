@@ -0,0 +1,773 @@
+//! The `time` module: clocks, dates and strftime-like formatting and parsing.
+//!
+//! Timestamps are ints counting milliseconds since the Unix epoch. Dates are broken
+//! down in UTC or at a fixed offset, given in minutes east of UTC; there are no time
+//! zone rules. Every clock reading goes through the interpreter's [`Clock`], so
+//! embedders can swap in a [`FakeClock`] and get the same answers on every run.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::lexer::LiteralTypes;
+
+use super::{
+    callable::{Callable, NativeError},
+    exception::ErrorKind,
+    Map,
+};
+
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// A hundred million days either side of the epoch, as in JavaScript, which keeps
+/// every date calculation well inside `i64`.
+const MAX_TIMESTAMP: i64 = 100_000_000 * MS_PER_DAY;
+
+/// Offsets beyond a day are surely mistakes.
+const MAX_OFFSET: i64 = 24 * 60 - 1;
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Where the interpreter gets the time from.
+pub trait Clock {
+    /// Milliseconds since the Unix epoch.
+    fn now(&self) -> i64;
+    /// Milliseconds since some fixed point; never goes backwards.
+    fn monotonic(&self) -> f64;
+    fn sleep(&self, ms: f64);
+}
+
+/// The real clocks of the machine.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_millis() as i64,
+            Err(before) => -(before.duration().as_millis() as i64),
+        }
+    }
+
+    fn monotonic(&self) -> f64 {
+        self.start.elapsed().as_secs_f64() * 1000.0
+    }
+
+    fn sleep(&self, ms: f64) {
+        // the `sleep` native refuses anything this long, but other callers might not
+        let duration = Duration::try_from_secs_f64(ms / 1000.0).unwrap_or(Duration::MAX);
+        thread::sleep(duration);
+    }
+}
+
+/// A clock that stands still until it is told to move. Sleeping moves it forward
+/// at once, so scripts that wait run instantly.
+#[derive(Debug, Default)]
+pub struct FakeClock {
+    now: Cell<i64>,
+    elapsed: Cell<f64>,
+}
+
+impl FakeClock {
+    /// A clock reading `now` milliseconds since the epoch.
+    pub fn new(now: i64) -> Self {
+        Self {
+            now: Cell::new(now),
+            elapsed: Cell::new(0.0),
+        }
+    }
+
+    pub fn advance(&self, ms: f64) {
+        // `as` saturates, so a huge `ms` pins the clock at the end of time
+        self.now.set(self.now.get().saturating_add(ms as i64));
+        self.elapsed.set(self.elapsed.get() + ms);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> i64 {
+        self.now.get()
+    }
+
+    fn monotonic(&self) -> f64 {
+        self.elapsed.get()
+    }
+
+    fn sleep(&self, ms: f64) {
+        self.advance(ms);
+    }
+}
+
+/// A timestamp broken down at some offset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Date {
+    pub year: i64,
+    /// 1 to 12
+    pub month: i64,
+    /// 1 to 31
+    pub day: i64,
+    pub hour: i64,
+    pub minute: i64,
+    pub second: i64,
+    pub millisecond: i64,
+    /// Minutes east of UTC.
+    pub offset: i64,
+}
+
+impl Date {
+    pub fn from_timestamp(ms: i64, offset: i64) -> Self {
+        let local = ms + offset * 60_000;
+        let (year, month, day) = civil_from_days(local.div_euclid(MS_PER_DAY));
+        let time = local.rem_euclid(MS_PER_DAY);
+        Date {
+            year,
+            month,
+            day,
+            hour: time / 3_600_000,
+            minute: time / 60_000 % 60,
+            second: time / 1000 % 60,
+            millisecond: time % 1000,
+            offset,
+        }
+    }
+
+    pub fn timestamp(&self) -> i64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let time = ((self.hour * 60 + self.minute) * 60 + self.second) * 1000 + self.millisecond;
+        days * MS_PER_DAY + time - self.offset * 60_000
+    }
+
+    /// 1 for Monday to 7 for Sunday.
+    pub fn weekday(&self) -> i64 {
+        // the epoch was a Thursday
+        (days_from_civil(self.year, self.month, self.day) + 3).rem_euclid(7) + 1
+    }
+
+    /// 1 for the first of January.
+    pub fn yearday(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1) + 1
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let fields = [
+            ("month", self.month, 1, 12),
+            ("day", self.day, 1, days_in_month(self.year, self.month)),
+            ("hour", self.hour, 0, 23),
+            ("minute", self.minute, 0, 59),
+            ("second", self.second, 0, 59),
+            ("millisecond", self.millisecond, 0, 999),
+            ("offset", self.offset, -MAX_OFFSET, MAX_OFFSET),
+        ];
+        for (name, value, low, high) in fields {
+            if !(low..=high).contains(&value) {
+                return Err(format!(
+                    "The {} must be between {} and {}, got {}.",
+                    name, low, high, value
+                ));
+            }
+        }
+        check_timestamp(self.timestamp()).map(|_| ())
+    }
+
+    /// Render the date per `format`: `%Y-%m-%d %H:%M:%S` and so on.
+    pub fn format(&self, format: &str) -> Result<String, String> {
+        let mut out = String::new();
+        let format = expand(format);
+        let mut chars = format.chars();
+        while let Some(char) = chars.next() {
+            if char != '%' {
+                out.push(char);
+                continue;
+            }
+            let Some(directive) = chars.next() else {
+                return Err("Format ends with a lone '%'.".to_string());
+            };
+            let text = match directive {
+                'Y' => format!("{:04}", self.year),
+                'y' => format!("{:02}", self.year.rem_euclid(100)),
+                'm' => format!("{:02}", self.month),
+                'd' => format!("{:02}", self.day),
+                'H' => format!("{:02}", self.hour),
+                'I' => format!("{:02}", (self.hour + 11) % 12 + 1),
+                'p' => (if self.hour < 12 { "AM" } else { "PM" }).to_string(),
+                'M' => format!("{:02}", self.minute),
+                'S' => format!("{:02}", self.second),
+                'f' => format!("{:03}", self.millisecond),
+                'j' => format!("{:03}", self.yearday()),
+                'A' => WEEKDAYS[self.weekday() as usize - 1].to_string(),
+                'a' => WEEKDAYS[self.weekday() as usize - 1][..3].to_string(),
+                'B' => MONTHS[self.month as usize - 1].to_string(),
+                'b' => MONTHS[self.month as usize - 1][..3].to_string(),
+                'z' => {
+                    let sign = if self.offset < 0 { '-' } else { '+' };
+                    let offset = self.offset.abs();
+                    format!("{}{:02}{:02}", sign, offset / 60, offset % 60)
+                }
+                's' => self.timestamp().div_euclid(1000).to_string(),
+                '%' => "%".to_string(),
+                other => return Err(format!("Unknown format directive '%{}'.", other)),
+            };
+            out.push_str(&text);
+        }
+        Ok(out)
+    }
+
+    /// Read `text` laid out per `format`. Fields the format leaves out default to the
+    /// start of their range, and the offset to `offset` unless there is a `%z`.
+    pub fn parse(text: &str, format: &str, offset: i64) -> Result<Self, String> {
+        let mut reader = Reader {
+            text: text.chars().collect(),
+            at: 0,
+        };
+        let mut date = Date {
+            year: 1970,
+            month: 1,
+            day: 1,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            millisecond: 0,
+            offset,
+        };
+        let mut yearday = None;
+        let mut pm = None;
+        let mut seconds = None;
+
+        let format = expand(format);
+        let mut chars = format.chars();
+        while let Some(char) = chars.next() {
+            if char != '%' {
+                reader.literal(char)?;
+                continue;
+            }
+            let Some(directive) = chars.next() else {
+                return Err("Format ends with a lone '%'.".to_string());
+            };
+            match directive {
+                'Y' => date.year = reader.signed("year", 6)?,
+                'y' => {
+                    let year = reader.number("year", 2, 2)?;
+                    // as POSIX has it: 69 to 99 are the 1900s, the rest the 2000s
+                    date.year = if year >= 69 { 1900 + year } else { 2000 + year };
+                }
+                'm' => date.month = reader.number("month", 1, 2)?,
+                'd' => date.day = reader.number("day", 1, 2)?,
+                'H' => date.hour = reader.number("hour", 1, 2)?,
+                'I' => {
+                    date.hour = reader.number("hour", 1, 2)?;
+                    if !(1..=12).contains(&date.hour) {
+                        return Err(format!(
+                            "The hour must be between 1 and 12, got {}.",
+                            date.hour
+                        ));
+                    }
+                }
+                'p' => pm = Some(reader.name("AM or PM", &["AM", "PM"])? == 1),
+                'M' => date.minute = reader.number("minute", 1, 2)?,
+                'S' => date.second = reader.number("second", 1, 2)?,
+                'f' => date.millisecond = reader.number("millisecond", 3, 3)?,
+                'j' => yearday = Some(reader.number("day of the year", 1, 3)?),
+                'A' => {
+                    reader.name("weekday", &WEEKDAYS)?;
+                }
+                'a' => {
+                    let short = WEEKDAYS.map(|day| &day[..3]);
+                    reader.name("weekday", &short)?;
+                }
+                'B' => date.month = reader.name("month name", &MONTHS)? as i64 + 1,
+                'b' => {
+                    let short = MONTHS.map(|month| &month[..3]);
+                    date.month = reader.name("month name", &short)? as i64 + 1;
+                }
+                'z' => date.offset = reader.offset()?,
+                's' => seconds = Some(reader.signed("seconds", 19)?),
+                '%' => reader.literal('%')?,
+                other => return Err(format!("Unknown format directive '%{}'.", other)),
+            }
+        }
+        if reader.at < reader.text.len() {
+            return Err(format!(
+                "Unexpected '{}' at position {}.",
+                reader.text[reader.at..].iter().collect::<String>(),
+                reader.at
+            ));
+        }
+
+        if let Some(seconds) = seconds {
+            let ms = check_timestamp(seconds.saturating_mul(1000))?;
+            return Ok(Date::from_timestamp(ms, date.offset));
+        }
+        if let Some(pm) = pm {
+            date.hour = date.hour % 12 + if pm { 12 } else { 0 };
+        }
+        if let Some(yearday) = yearday {
+            let days = if days_in_month(date.year, 2) == 29 {
+                366
+            } else {
+                365
+            };
+            if !(1..=days).contains(&yearday) {
+                return Err(format!(
+                    "The day of the year must be between 1 and {}, got {}.",
+                    days, yearday
+                ));
+            }
+            let (year, month, day) =
+                civil_from_days(days_from_civil(date.year, 1, 1) + yearday - 1);
+            (date.year, date.month, date.day) = (year, month, day);
+        }
+        date.check()?;
+        Ok(date)
+    }
+}
+
+struct Reader {
+    text: Vec<char>,
+    at: usize,
+}
+
+impl Reader {
+    fn error(&self, expected: &str) -> String {
+        match self.text.get(self.at) {
+            Some(char) => format!(
+                "Expected {} but found '{}' at position {}.",
+                expected, char, self.at
+            ),
+            None => format!(
+                "Expected {} but the text ended at position {}.",
+                expected, self.at
+            ),
+        }
+    }
+
+    fn literal(&mut self, expected: char) -> Result<(), String> {
+        if self.text.get(self.at) != Some(&expected) {
+            return Err(self.error(&format!("'{}'", expected)));
+        }
+        self.at += 1;
+        Ok(())
+    }
+
+    /// Between `min` and `max` digits.
+    fn number(&mut self, what: &str, min: usize, max: usize) -> Result<i64, String> {
+        let start = self.at;
+        let mut value = 0;
+        while self.at - start < max {
+            match self.text.get(self.at).and_then(|char| char.to_digit(10)) {
+                Some(digit) => value = value * 10 + digit as i64,
+                None => break,
+            }
+            self.at += 1;
+        }
+        if self.at - start < min {
+            self.at = start;
+            return Err(self.error(what));
+        }
+        Ok(value)
+    }
+
+    fn signed(&mut self, what: &str, max: usize) -> Result<i64, String> {
+        let negative = matches!(self.text.get(self.at), Some('-'));
+        if negative || matches!(self.text.get(self.at), Some('+')) {
+            self.at += 1;
+        }
+        let value = self.number(what, 1, max)?;
+        Ok(if negative { -value } else { value })
+    }
+
+    /// The index of whichever of `names` comes next, ignoring case.
+    fn name(&mut self, what: &str, names: &[&str]) -> Result<usize, String> {
+        for (index, name) in names.iter().enumerate() {
+            let end = self.at + name.chars().count();
+            let Some(candidate) = self.text.get(self.at..end) else {
+                continue;
+            };
+            let candidate: String = candidate.iter().collect();
+            if candidate.eq_ignore_ascii_case(name) {
+                self.at = end;
+                return Ok(index);
+            }
+        }
+        Err(self.error(what))
+    }
+
+    /// `Z`, `+hhmm` or `+hh:mm`.
+    fn offset(&mut self) -> Result<i64, String> {
+        let sign = match self.text.get(self.at) {
+            Some('Z') => {
+                self.at += 1;
+                return Ok(0);
+            }
+            Some('+') => 1,
+            Some('-') => -1,
+            _ => return Err(self.error("an offset")),
+        };
+        self.at += 1;
+        let hours = self.number("offset hours", 2, 2)?;
+        if self.text.get(self.at) == Some(&':') {
+            self.at += 1;
+        }
+        let minutes = self.number("offset minutes", 2, 2)?;
+        Ok(sign * (hours * 60 + minutes))
+    }
+}
+
+/// The format with `%F` and `%T` spelled out.
+fn expand(format: &str) -> String {
+    let mut expanded = String::new();
+    let mut chars = format.chars();
+    while let Some(char) = chars.next() {
+        if char != '%' {
+            expanded.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('F') => expanded.push_str("%Y-%m-%d"),
+            Some('T') => expanded.push_str("%H:%M:%S"),
+            Some(directive) => expanded.extend(['%', directive]),
+            None => expanded.push('%'),
+        }
+    }
+    expanded
+}
+
+fn check_timestamp(ms: i64) -> Result<i64, String> {
+    if ms.abs() > MAX_TIMESTAMP {
+        return Err(format!("The timestamp {} is out of range.", ms));
+    }
+    Ok(ms)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date, after Howard Hinnant's
+/// `days_from_civil`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+pub fn exports() -> Vec<(&'static str, LiteralTypes)> {
+    vec![
+        (
+            "now",
+            Callable::native("now", 0..=0, |interpreter, _| {
+                Ok(LiteralTypes::Int(interpreter.clock().now()))
+            }),
+        ),
+        (
+            "monotonic",
+            Callable::native("monotonic", 0..=0, |interpreter, _| {
+                Ok(LiteralTypes::Number(interpreter.clock().monotonic()))
+            }),
+        ),
+        (
+            "sleep",
+            Callable::native("sleep", 1..=1, |interpreter, args| {
+                let ms = match args[0].as_f64() {
+                    Some(ms) if (0.0..=MAX_TIMESTAMP as f64).contains(&ms) => ms,
+                    Some(ms) if ms > 0.0 => {
+                        return Err(NativeError::from(format!(
+                            "Can't sleep longer than {} ms.",
+                            MAX_TIMESTAMP
+                        )))
+                    }
+                    _ => {
+                        return Err(NativeError::type_error(format!(
+                            "sleep takes a non-negative number of milliseconds, got {}.",
+                            args[0].stringify()
+                        )))
+                    }
+                };
+                interpreter.clock().sleep(ms);
+                Ok(LiteralTypes::Nil)
+            }),
+        ),
+        (
+            "date",
+            Callable::native("date", 0..=2, |interpreter, args| {
+                let ms = match args.first() {
+                    None | Some(LiteralTypes::Nil) => interpreter.clock().now(),
+                    Some(ms) => timestamp(ms)?,
+                };
+                let date = Date::from_timestamp(ms, offset(args.get(1))?);
+                Ok(date_map(&date))
+            }),
+        ),
+        (
+            "timestamp",
+            Callable::native("timestamp", 1..=1, |_, args| {
+                let date = map_date(&args[0])?;
+                Ok(LiteralTypes::Int(date.timestamp()))
+            }),
+        ),
+        (
+            "format",
+            Callable::native("format", 2..=3, |_, args| {
+                let date = Date::from_timestamp(timestamp(&args[0])?, offset(args.get(2))?);
+                let text = date.format(&string("format", &args[1])?)?;
+                Ok(LiteralTypes::String(text))
+            }),
+        ),
+        (
+            "parse",
+            Callable::native("parse", 2..=3, |_, args| {
+                let text = string("text", &args[0])?;
+                let format = string("format", &args[1])?;
+                let date = Date::parse(&text, &format, offset(args.get(2))?)
+                    .map_err(|message| NativeError::Error(ErrorKind::Parse, message))?;
+                Ok(LiteralTypes::Int(date.timestamp()))
+            }),
+        ),
+    ]
+}
+
+fn timestamp(value: &LiteralTypes) -> Result<i64, NativeError> {
+    match value {
+        LiteralTypes::Int(ms) => Ok(check_timestamp(*ms)?),
+        LiteralTypes::Number(ms) if ms.abs() <= MAX_TIMESTAMP as f64 => Ok(ms.floor() as i64),
+        LiteralTypes::Number(ms) if ms.is_finite() => Err(NativeError::from(format!(
+            "The timestamp {} is out of range.",
+            ms
+        ))),
+        other => Err(NativeError::type_error(format!(
+            "Timestamp must be a number of milliseconds, got {}.",
+            other.stringify()
+        ))),
+    }
+}
+
+/// The optional offset argument, in minutes east of UTC.
+fn offset(value: Option<&LiteralTypes>) -> Result<i64, NativeError> {
+    match value {
+        None | Some(LiteralTypes::Nil) => Ok(0),
+        Some(LiteralTypes::Int(minutes)) if minutes.abs() <= MAX_OFFSET => Ok(*minutes),
+        Some(other) => Err(NativeError::type_error(format!(
+            "Offset must be an int between {} and {} minutes, got {}.",
+            -MAX_OFFSET,
+            MAX_OFFSET,
+            other.stringify()
+        ))),
+    }
+}
+
+fn string(what: &str, value: &LiteralTypes) -> Result<String, NativeError> {
+    match value {
+        LiteralTypes::String(text) => Ok(text.clone()),
+        other => Err(NativeError::type_error(format!(
+            "The {} must be a string, got {}.",
+            what,
+            other.type_name()
+        ))),
+    }
+}
+
+fn date_map(date: &Date) -> LiteralTypes {
+    let mut map = Map::default();
+    for (key, value) in [
+        ("year", date.year),
+        ("month", date.month),
+        ("day", date.day),
+        ("hour", date.hour),
+        ("minute", date.minute),
+        ("second", date.second),
+        ("millisecond", date.millisecond),
+        ("weekday", date.weekday()),
+        ("yearday", date.yearday()),
+        ("offset", date.offset),
+    ] {
+        // string keys always hash
+        let _ = map.insert(
+            LiteralTypes::String(key.to_string()),
+            LiteralTypes::Int(value),
+        );
+    }
+    LiteralTypes::Map(Rc::new(RefCell::new(map)))
+}
+
+/// A date from a map like `time.date` returns. The year, month and day are required;
+/// the rest default to zero, and `weekday` and `yearday` are ignored.
+fn map_date(value: &LiteralTypes) -> Result<Date, NativeError> {
+    let LiteralTypes::Map(map) = value else {
+        return Err(NativeError::type_error(format!(
+            "Expected a date map, got {}.",
+            value.type_name()
+        )));
+    };
+    let map = map.borrow();
+    let field = |name: &str, default: Option<i64>| {
+        let key = LiteralTypes::String(name.to_string());
+        match (map.get(&key), default) {
+            (Ok(Some(LiteralTypes::Int(value))), _) => Ok(value),
+            (Ok(None), Some(default)) => Ok(default),
+            (Ok(None), None) => Err(NativeError::type_error(format!(
+                "The date has no '{}'.",
+                name
+            ))),
+            (_, _) => Err(NativeError::type_error(format!(
+                "The date's '{}' must be an int.",
+                name
+            ))),
+        }
+    };
+    let date = Date {
+        year: field("year", None)?,
+        month: field("month", None)?,
+        day: field("day", None)?,
+        hour: field("hour", Some(0))?,
+        minute: field("minute", Some(0))?,
+        second: field("second", Some(0))?,
+        millisecond: field("millisecond", Some(0))?,
+        offset: field("offset", Some(0))?,
+    };
+    date.check().map_err(NativeError::type_error)?;
+    Ok(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_days_both_ways() {
+        for (days, date) in [
+            (0, (1970, 1, 1)),
+            (-1, (1969, 12, 31)),
+            (11_016, (2000, 2, 29)),
+            (-719_468, (0, 3, 1)),
+            (2_932_896, (9999, 12, 31)),
+        ] {
+            assert_eq!(civil_from_days(days), date);
+            assert_eq!(days_from_civil(date.0, date.1, date.2), days);
+        }
+        // every day of four centuries, leap years included
+        for days in -73_000..73_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn fake_clock_saturates() {
+        let clock = FakeClock::new(i64::MAX - 1);
+        clock.sleep(1e30);
+        assert_eq!(clock.now(), i64::MAX);
+        assert_eq!(clock.monotonic(), 1e30);
+    }
+
+    #[test]
+    fn formats_and_parses_back() {
+        let date = Date::from_timestamp(951_827_696_789, 330);
+        assert_eq!(
+            date.format("%a %d %b %Y %T.%f %z %j %I%p").unwrap(),
+            "Tue 29 Feb 2000 18:04:56.789 +0530 060 06PM"
+        );
+        assert_eq!(date.weekday(), 2);
+
+        let parsed = Date::parse(
+            "Tue 29 Feb 2000 18:04:56.789 +0530",
+            "%a %d %b %Y %T.%f %z",
+            0,
+        );
+        assert_eq!(parsed.unwrap(), date);
+        let parsed = Date::parse("2000-060 06:04:56PM", "%Y-%j %I:%M:%S%p", 330).unwrap();
+        assert_eq!(parsed.timestamp(), 951_827_696_000);
+        let parsed = Date::parse("951827696", "%s", 0).unwrap();
+        assert_eq!(parsed.timestamp(), 951_827_696_000);
+    }
+
+    #[test]
+    fn parse_errors_say_where() {
+        for (text, format, message) in [
+            (
+                "2023-02-29",
+                "%F",
+                "The day must be between 1 and 28, got 29.",
+            ),
+            (
+                "2023-1x-01",
+                "%F",
+                "Expected '-' but found 'x' at position 6.",
+            ),
+            ("2023-01-01 x", "%F", "Unexpected ' x' at position 10."),
+            (
+                "12:00",
+                "%H:%M:%S",
+                "Expected ':' but the text ended at position 5.",
+            ),
+            (
+                "Jum",
+                "%b",
+                "Expected month name but found 'J' at position 0.",
+            ),
+            ("2023", "%Q", "Unknown format directive '%Q'."),
+        ] {
+            assert_eq!(Date::parse(text, format, 0).unwrap_err(), message);
+        }
+    }
+}
@@ -0,0 +1,594 @@
+//! Pattern syntax and its compilation to a [`vm`](super::vm) program.
+//!
+//! ```text
+//! alternation → concat ( "|" concat )*
+//! concat      → repeat*
+//! repeat      → atom ( ( "*" | "+" | "?" | "{" n ( "," m? )? "}" ) "?"? )*
+//! atom        → "(" ( "?:" )? alternation ")" | "[" "^"? item+ "]" | "." | "^" | "$"
+//!             | "\" escape | char
+//! ```
+
+use super::{
+    vm::{self, Assertion, Class, ClassItem, Inst},
+    Captures,
+};
+
+/// The most a single `{n,m}` may repeat.
+const MAX_REPEAT: u32 = 1000;
+/// The most instructions a pattern may compile to.
+const MAX_PROGRAM: usize = 100_000;
+
+#[derive(Debug)]
+pub struct Regex {
+    pattern: String,
+    program: Vec<Inst>,
+    /// Capture groups, not counting the whole match.
+    groups: usize,
+}
+
+/// Two regexes are the same if they were compiled from the same pattern.
+impl PartialEq for Regex {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            at: 0,
+            groups: 0,
+        };
+        let node = parser.alternation()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("Unmatched ')'"));
+        }
+
+        let mut compiler = Compiler {
+            program: Vec::new(),
+        };
+        compiler.emit(Inst::Save(0))?;
+        compiler.node(&node)?;
+        compiler.emit(Inst::Save(1))?;
+        compiler.emit(Inst::Match)?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            program: compiler.program,
+            groups: parser.groups,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn groups(&self) -> usize {
+        self.groups
+    }
+
+    /// The leftmost match that starts at or after `start`.
+    pub fn find_at(&self, text: &[char], start: usize) -> Option<Captures> {
+        let slots = vm::exec(&self.program, 2 * (self.groups + 1), text, start)?;
+        let spans = slots
+            .chunks(2)
+            .map(|pair| match pair {
+                [Some(start), Some(end)] => Some((*start, *end)),
+                _ => None,
+            })
+            .collect();
+        Some(spans)
+    }
+
+    /// Every non-overlapping match, left to right. After an empty match the search
+    /// resumes one char further on, so it can't find the same empty match forever.
+    pub fn find_all(&self, text: &[char]) -> Vec<Captures> {
+        let mut found = Vec::new();
+        let mut at = 0;
+        while at <= text.len() {
+            let Some(captures) = self.find_at(text, at) else {
+                break;
+            };
+            let (start, end) = captures[0].unwrap_or_default();
+            at = if start == end { end + 1 } else { end };
+            found.push(captures);
+        }
+        found
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    /// A parenthesised group, capturing into the numbered slot pair unless `(?:...)`.
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+        greedy: bool,
+    },
+}
+
+struct Parser {
+    chars: Vec<char>,
+    at: usize,
+    groups: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.at).copied()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.at += 1;
+        }
+        found
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at position {}.", message, self.at)
+    }
+
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        Ok(match branches.len() {
+            1 => branches.pop().unwrap_or(Node::Empty),
+            _ => Node::Alternation(branches),
+        })
+    }
+
+    fn concat(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+        while !matches!(self.peek(), None | Some('|' | ')')) {
+            nodes.push(self.repeat()?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap_or(Node::Empty),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn repeat(&mut self) -> Result<Node, String> {
+        let mut node = self.atom()?;
+        loop {
+            let (min, max) = match self.peek() {
+                Some('{') => match self.counts()? {
+                    Some(counts) => counts,
+                    None => break,
+                },
+                Some(quantifier @ ('*' | '+' | '?')) => {
+                    self.at += 1;
+                    match quantifier {
+                        '*' => (0, None),
+                        '+' => (1, None),
+                        _ => (0, Some(1)),
+                    }
+                }
+                _ => break,
+            };
+            let greedy = !self.eat('?');
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+                greedy,
+            };
+        }
+        Ok(node)
+    }
+
+    /// `{n}`, `{n,}` or `{n,m}`, consumed only when well formed; otherwise the `{` is
+    /// an ordinary char.
+    fn counts(&mut self) -> Result<Option<(u32, Option<u32>)>, String> {
+        let start = self.at;
+        self.at += 1;
+        let number = |parser: &mut Self| {
+            let digits_start = parser.at;
+            while parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+                parser.at += 1;
+            }
+            let digits: String = parser.chars[digits_start..parser.at].iter().collect();
+            digits.parse::<u32>().ok()
+        };
+
+        let min = number(self);
+        let max = match self.eat(',') {
+            true => number(self),
+            false => min,
+        };
+        let (Some(min), true) = (min, self.eat('}')) else {
+            self.at = start;
+            return Ok(None);
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(self.error("Repeat maximum is below its minimum"));
+        }
+        if min.max(max.unwrap_or(0)) > MAX_REPEAT {
+            return Err(self.error(&format!("Repeat count is above {}", MAX_REPEAT)));
+        }
+        Ok(Some((min, max)))
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let Some(char) = self.peek() else {
+            return Err(self.error("Unexpected end of pattern"));
+        };
+        self.at += 1;
+        let node = match char {
+            '(' => {
+                let index = match self.eat('?') {
+                    true if self.eat(':') => None,
+                    true => return Err(self.error("Unknown group flag")),
+                    false => {
+                        self.groups += 1;
+                        Some(self.groups)
+                    }
+                };
+                let inner = self.alternation()?;
+                if !self.eat(')') {
+                    return Err(self.error("Missing ')'"));
+                }
+                Node::Group(Box::new(inner), index)
+            }
+            '[' => Node::Class(self.class()?),
+            '.' => Node::Any,
+            '^' => Node::Assert(Assertion::Start),
+            '$' => Node::Assert(Assertion::End),
+            '*' | '+' | '?' => {
+                self.at -= 1;
+                return Err(self.error("Nothing to repeat"));
+            }
+            '\\' => match self.escape()? {
+                Escape::Char(char) => Node::Char(char),
+                Escape::Class(class) => Node::Class(class),
+                Escape::Assert(assertion) => Node::Assert(assertion),
+            },
+            char => Node::Char(char),
+        };
+        Ok(node)
+    }
+
+    /// What follows `[`, up to and including the `]`.
+    fn class(&mut self) -> Result<Class, String> {
+        let mut class = Class {
+            negated: self.eat('^'),
+            items: Vec::new(),
+        };
+        let mut first = true;
+        loop {
+            let low = match self.peek() {
+                None => return Err(self.error("Missing ']'")),
+                Some(']') if !first => {
+                    self.at += 1;
+                    return Ok(class);
+                }
+                Some(_) => self.class_char()?,
+            };
+            first = false;
+            let low = match low {
+                Escape::Char(low) => low,
+                Escape::Class(shorthand) => {
+                    class.items.push(ClassItem::Class(shorthand));
+                    continue;
+                }
+                Escape::Assert(_) => unreachable!("class_char never returns assertions"),
+            };
+
+            let is_range = self.peek() == Some('-')
+                && !matches!(self.chars.get(self.at + 1), None | Some(']'));
+            if !is_range {
+                class.items.push(ClassItem::Range(low, low));
+                continue;
+            }
+            self.at += 1;
+            let Escape::Char(high) = self.class_char()? else {
+                return Err(self.error("Invalid range end"));
+            };
+            if high < low {
+                return Err(self.error("Range is out of order"));
+            }
+            class.items.push(ClassItem::Range(low, high));
+        }
+    }
+
+    fn class_char(&mut self) -> Result<Escape, String> {
+        let char = self.peek().unwrap_or_default();
+        self.at += 1;
+        if char != '\\' {
+            return Ok(Escape::Char(char));
+        }
+        match self.escape()? {
+            Escape::Assert(Assertion::WordBoundary) => Ok(Escape::Char('\u{8}')),
+            Escape::Assert(_) => Err(self.error("Assertions can't appear in a class")),
+            escape => Ok(escape),
+        }
+    }
+
+    /// What follows a `\`.
+    fn escape(&mut self) -> Result<Escape, String> {
+        let Some(char) = self.peek() else {
+            return Err(self.error("Pattern ends with '\\'"));
+        };
+        self.at += 1;
+        let shorthand =
+            |negated: bool, items: Vec<ClassItem>| Ok(Escape::Class(Class { negated, items }));
+        match char {
+            'd' | 'D' => shorthand(char == 'D', digit()),
+            'w' | 'W' => shorthand(char == 'W', word()),
+            's' | 'S' => shorthand(char == 'S', space()),
+            'b' => Ok(Escape::Assert(Assertion::WordBoundary)),
+            'B' => Ok(Escape::Assert(Assertion::NotWordBoundary)),
+            'n' => Ok(Escape::Char('\n')),
+            't' => Ok(Escape::Char('\t')),
+            'r' => Ok(Escape::Char('\r')),
+            'f' => Ok(Escape::Char('\u{c}')),
+            'v' => Ok(Escape::Char('\u{b}')),
+            char if char.is_alphanumeric() => {
+                self.at -= 1;
+                Err(self.error(&format!("Unknown escape '\\{}'", char)))
+            }
+            char => Ok(Escape::Char(char)),
+        }
+    }
+}
+
+enum Escape {
+    Char(char),
+    Class(Class),
+    Assert(Assertion),
+}
+
+fn digit() -> Vec<ClassItem> {
+    vec![ClassItem::Range('0', '9')]
+}
+
+fn word() -> Vec<ClassItem> {
+    vec![ClassItem::Word]
+}
+
+fn space() -> Vec<ClassItem> {
+    vec![ClassItem::Space]
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn emit(&mut self, inst: Inst) -> Result<usize, String> {
+        if self.program.len() == MAX_PROGRAM {
+            return Err("Pattern is too large.".to_string());
+        }
+        self.program.push(inst);
+        Ok(self.program.len() - 1)
+    }
+
+    fn node(&mut self, node: &Node) -> Result<(), String> {
+        match node {
+            Node::Empty => {}
+            Node::Char(char) => {
+                self.emit(Inst::Char(*char))?;
+            }
+            Node::Any => {
+                self.emit(Inst::Any)?;
+            }
+            Node::Class(class) => {
+                self.emit(Inst::Class(class.clone()))?;
+            }
+            Node::Assert(assertion) => {
+                self.emit(Inst::Assert(*assertion))?;
+            }
+            Node::Group(inner, None) => self.node(inner)?,
+            Node::Group(inner, Some(index)) => {
+                self.emit(Inst::Save(2 * index))?;
+                self.node(inner)?;
+                self.emit(Inst::Save(2 * index + 1))?;
+            }
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.node(node)?;
+                }
+            }
+            Node::Alternation(branches) => {
+                let mut jumps = Vec::new();
+                for (i, branch) in branches.iter().enumerate() {
+                    if i + 1 == branches.len() {
+                        self.node(branch)?;
+                        break;
+                    }
+                    let split = self.emit(Inst::Split(0, 0))?;
+                    self.node(branch)?;
+                    jumps.push(self.emit(Inst::Jump(0))?);
+                    self.program[split] = Inst::Split(split + 1, self.program.len());
+                }
+                for jump in jumps {
+                    self.program[jump] = Inst::Jump(self.program.len());
+                }
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => {
+                for _ in 0..*min {
+                    self.node(node)?;
+                }
+                let split = |body: usize, end: usize| match greedy {
+                    true => Inst::Split(body, end),
+                    false => Inst::Split(end, body),
+                };
+                match max {
+                    None => {
+                        let start = self.emit(Inst::Split(0, 0))?;
+                        self.node(node)?;
+                        self.emit(Inst::Jump(start))?;
+                        self.program[start] = split(start + 1, self.program.len());
+                    }
+                    Some(max) => {
+                        let mut splits = Vec::new();
+                        for _ in *min..*max {
+                            splits.push(self.emit(Inst::Split(0, 0))?);
+                            self.node(node)?;
+                        }
+                        let end = self.program.len();
+                        for at in splits {
+                            self.program[at] = split(at + 1, end);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The whole match and each group of the first match of `pattern` in `text`.
+    fn find(pattern: &str, text: &str) -> Option<Vec<Option<String>>> {
+        let chars: Vec<char> = text.chars().collect();
+        let captures = Regex::new(pattern).unwrap().find_at(&chars, 0)?;
+        let spans = captures
+            .into_iter()
+            .map(|span| span.map(|(start, end)| chars[start..end].iter().collect()))
+            .collect();
+        Some(spans)
+    }
+
+    fn matched(pattern: &str, text: &str) -> Option<String> {
+        find(pattern, text).and_then(|spans| spans[0].clone())
+    }
+
+    #[test]
+    fn matches() {
+        for (pattern, text, expected) in [
+            ("abc", "xxabcxx", Some("abc")),
+            ("a.c", "a\nc abc", Some("abc")),
+            ("colou?r", "my color", Some("color")),
+            ("\\d+", "ab 123 45", Some("123")),
+            ("\\d{2,3}", "1 12345", Some("123")),
+            ("\\d{2}", "1 2", None),
+            ("\\d{2,}", "12345", Some("12345")),
+            ("x{,2}", "x{,2}", Some("x{,2}")),
+            ("[a-c]+", "xxbcaby", Some("bcab")),
+            ("[^a-c ]+", "abc def", Some("def")),
+            ("[]a]+", "x]a]", Some("]a]")),
+            ("[\\d_-]+", "ab 1_-2 c", Some("1_-2")),
+            (
+                "\\w+@\\w+\\.com",
+                "mail ada@lox.com now",
+                Some("ada@lox.com"),
+            ),
+            ("\\s+", "a \t b", Some(" \t ")),
+            ("^ab", "cab", None),
+            ("ab$", "abc ab", Some("ab")),
+            ("\\bcat\\b", "concat cat", Some("cat")),
+            ("\\Bcat", "cat concat", Some("cat")),
+            ("cat|dog", "hotdog", Some("dog")),
+            ("a|ab", "ab", Some("a")),
+            ("(a|ab)c", "abc", Some("abc")),
+            ("a*", "baa", Some("")),
+            ("a+?", "aaa", Some("a")),
+            ("<.*>", "<a><b>", Some("<a><b>")),
+            ("<.*?>", "<a><b>", Some("<a>")),
+            ("é+", "héé", Some("éé")),
+            ("\\w+", "¿señor?", Some("señor")),
+            ("\\bñ", "año ñu", Some("ñ")),
+            ("(a*)*b", "aaab", Some("aaab")),
+            ("\\.\\*", "a.*b", Some(".*")),
+        ] {
+            assert_eq!(
+                matched(pattern, text).as_deref(),
+                expected,
+                "{} in {:?}",
+                pattern,
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn groups() {
+        let spans = find("(\\d+)-(\\d+)(?:-(x))?", "tel 555-1234").unwrap();
+        assert_eq!(
+            spans,
+            vec![
+                Some("555-1234".to_string()),
+                Some("555".to_string()),
+                Some("1234".to_string()),
+                None
+            ]
+        );
+        assert_eq!(Regex::new("(a)(?:b)(c(d))").unwrap().groups(), 3);
+        // the last iteration of a repeated group is what it captures
+        assert_eq!(find("(\\w)+", "abc").unwrap()[1].as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn find_all_steps_past_empty_matches() {
+        let regex = Regex::new("a*").unwrap();
+        let chars: Vec<char> = "baa".chars().collect();
+        let spans: Vec<_> = regex
+            .find_all(&chars)
+            .into_iter()
+            .map(|captures| captures[0].unwrap())
+            .collect();
+        assert_eq!(spans, vec![(0, 0), (1, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn no_exponential_blowup() {
+        let text: Vec<char> = "a".repeat(30).chars().collect();
+        let regex = Regex::new("(a?){30}a{30}").unwrap();
+        assert!(regex.find_at(&text, 0).is_some());
+        let regex = Regex::new("(a|aa)*b").unwrap();
+        assert!(regex.find_at(&text, 0).is_none());
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        for (pattern, message) in [
+            ("(a", "Missing ')' at position 2."),
+            ("a)", "Unmatched ')' at position 1."),
+            ("[a", "Missing ']' at position 2."),
+            ("*a", "Nothing to repeat at position 0."),
+            ("a|?", "Nothing to repeat at position 2."),
+            (
+                "a{3,1}",
+                "Repeat maximum is below its minimum at position 6.",
+            ),
+            ("a{1001}", "Repeat count is above 1000 at position 7."),
+            ("[z-a]", "Range is out of order at position 4."),
+            ("\\q", "Unknown escape '\\q' at position 1."),
+            ("a\\", "Pattern ends with '\\' at position 2."),
+            ("(?=a)", "Unknown group flag at position 2."),
+            (
+                "[\\b\\B]",
+                "Assertions can't appear in a class at position 5.",
+            ),
+        ] {
+            assert_eq!(Regex::new(pattern).unwrap_err(), message, "{}", pattern);
+        }
+        assert_eq!(
+            Regex::new("(a{1000}){1000}").unwrap_err(),
+            "Pattern is too large."
+        );
+    }
+}
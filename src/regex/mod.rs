@@ -0,0 +1,12 @@
+//! Regular expressions backing the `re` module: patterns compile to a Thompson NFA that
+//! is run as a Pike VM, so matching never backtracks and takes time linear in the text.
+//!
+//! Matching works on chars, so every position is a char offset like the string methods'.
+
+pub use compile::Regex;
+
+mod compile;
+mod vm;
+
+/// Where group `i` matched is `spans[i]`; group 0 is the whole match.
+pub type Captures = Vec<Option<(usize, usize)>>;
@@ -0,0 +1,202 @@
+//! The Pike VM: every NFA thread advances in lockstep over the text, one char at a time.
+//!
+//! Threads are kept in priority order, so the first to reach `Match` is the match a
+//! backtracking engine would have found, without the exponential worst case.
+
+/// One instruction of a compiled pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inst {
+    Char(char),
+    /// Any char but a newline.
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    /// Continue at both targets, preferring the first.
+    Split(usize, usize),
+    Jump(usize),
+    /// Record the current position in a capture slot.
+    Save(usize),
+    Match,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Assertion {
+    /// `^`
+    Start,
+    /// `$`
+    End,
+    /// `\b`
+    WordBoundary,
+    /// `\B`
+    NotWordBoundary,
+}
+
+/// `[a-z_]`, `[^0-9]` or a shorthand like `\d`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Class {
+    pub negated: bool,
+    pub items: Vec<ClassItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassItem {
+    Range(char, char),
+    /// `\w`: a letter, digit or `_` in any script.
+    Word,
+    /// `\s`: any Unicode whitespace.
+    Space,
+    /// A shorthand inside brackets, such as the `\D` of `[\D_]`.
+    Class(Class),
+}
+
+impl Class {
+    pub fn matches(&self, char: char) -> bool {
+        let found = self.items.iter().any(|item| match item {
+            ClassItem::Range(low, high) => (*low..=*high).contains(&char),
+            ClassItem::Word => is_word(char),
+            ClassItem::Space => char.is_whitespace(),
+            ClassItem::Class(class) => class.matches(char),
+        });
+        found != self.negated
+    }
+}
+
+impl Assertion {
+    fn holds(&self, text: &[char], at: usize) -> bool {
+        let word = |at: Option<usize>| at.and_then(|at| text.get(at)).is_some_and(|c| is_word(*c));
+        let boundary = word(at.checked_sub(1)) != word(Some(at));
+        match self {
+            Assertion::Start => at == 0,
+            Assertion::End => at == text.len(),
+            Assertion::WordBoundary => boundary,
+            Assertion::NotWordBoundary => !boundary,
+        }
+    }
+}
+
+fn is_word(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+struct Thread {
+    pc: usize,
+    slots: Vec<Option<usize>>,
+}
+
+/// The leftmost match starting at or after `start`, as the raw capture slots.
+pub fn exec(
+    program: &[Inst],
+    slot_count: usize,
+    text: &[char],
+    start: usize,
+) -> Option<Vec<Option<usize>>> {
+    let mut seen = vec![usize::MAX; program.len()];
+    let mut generation = 0;
+    let mut current = Vec::new();
+    let mut next = Vec::new();
+    let mut matched = None;
+
+    let mut at = start;
+    loop {
+        // a new thread per position until something matches, behind every older one
+        if matched.is_none() {
+            let slots = vec![None; slot_count];
+            add(
+                program,
+                &mut current,
+                &mut seen,
+                generation,
+                0,
+                slots,
+                text,
+                at,
+            );
+        }
+        if current.is_empty() {
+            if matched.is_some() || at == text.len() {
+                break;
+            }
+            generation += 1;
+            at += 1;
+            continue;
+        }
+
+        generation += 1;
+        let char = text.get(at).copied();
+        for thread in current.drain(..) {
+            let advances = match (&program[thread.pc], char) {
+                (Inst::Match, _) => {
+                    // everything after this thread has a lower priority
+                    matched = Some(thread.slots);
+                    break;
+                }
+                (_, None) => false,
+                (Inst::Char(expected), Some(char)) => *expected == char,
+                (Inst::Any, Some(char)) => char != '\n',
+                (Inst::Class(class), Some(char)) => class.matches(char),
+                _ => unreachable!("add only queues instructions that consume"),
+            };
+            if advances {
+                let pc = thread.pc + 1;
+                add(
+                    program,
+                    &mut next,
+                    &mut seen,
+                    generation,
+                    pc,
+                    thread.slots,
+                    text,
+                    at + 1,
+                );
+            }
+        }
+        std::mem::swap(&mut current, &mut next);
+        if at == text.len() {
+            break;
+        }
+        at += 1;
+    }
+
+    matched
+}
+
+/// Queue the thread at `pc`, following jumps, splits, saves and assertions right away
+/// so that only `Match` and instructions that consume a char are queued.
+#[allow(clippy::too_many_arguments)]
+fn add(
+    program: &[Inst],
+    list: &mut Vec<Thread>,
+    seen: &mut [usize],
+    generation: usize,
+    pc: usize,
+    slots: Vec<Option<usize>>,
+    text: &[char],
+    at: usize,
+) {
+    // an explicit stack, since long patterns would nest deeply; the preferred branch is
+    // pushed last so it is explored first
+    let mut stack = vec![(pc, slots)];
+    while let Some((pc, mut slots)) = stack.pop() {
+        if seen[pc] == generation {
+            continue;
+        }
+        seen[pc] = generation;
+        match &program[pc] {
+            Inst::Jump(to) => stack.push((*to, slots)),
+            Inst::Split(first, second) => {
+                stack.push((*second, slots.clone()));
+                stack.push((*first, slots));
+            }
+            Inst::Save(slot) => {
+                slots[*slot] = Some(at);
+                stack.push((pc + 1, slots));
+            }
+            Inst::Assert(assertion) => {
+                if assertion.holds(text, at) {
+                    stack.push((pc + 1, slots));
+                }
+            }
+            _ => list.push(Thread { pc, slots }),
+        }
+    }
+}